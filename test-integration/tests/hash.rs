@@ -4,6 +4,7 @@ mod backend;
 
 use backend::get_db_connection;
 use mysql::prelude::*;
+use mysql::Value;
 
 const SETUP: &[&str] = &[
     "create or replace function blake2b512 returns string
@@ -70,6 +71,66 @@ const SETUP: &[&str] = &[
         soname 'libudf_hash.so'",
     "create or replace function sha3_512_bin returns string
         soname 'libudf_hash.so'",
+    "create or replace function sha512_224 returns string
+        soname 'libudf_hash.so'",
+    "create or replace function sha512_224_bin returns string
+        soname 'libudf_hash.so'",
+    "create or replace function sha512_256 returns string
+        soname 'libudf_hash.so'",
+    "create or replace function sha512_256_bin returns string
+        soname 'libudf_hash.so'",
+    "create or replace function ripemd160 returns string
+        soname 'libudf_hash.so'",
+    "create or replace function ripemd160_bin returns string
+        soname 'libudf_hash.so'",
+    "create or replace function sm3 returns string
+        soname 'libudf_hash.so'",
+    "create or replace function sm3_bin returns string
+        soname 'libudf_hash.so'",
+    "create or replace function streebog256 returns string
+        soname 'libudf_hash.so'",
+    "create or replace function streebog256_bin returns string
+        soname 'libudf_hash.so'",
+    "create or replace function streebog512 returns string
+        soname 'libudf_hash.so'",
+    "create or replace function streebog512_bin returns string
+        soname 'libudf_hash.so'",
+    "create or replace function whirlpool returns string
+        soname 'libudf_hash.so'",
+    "create or replace function whirlpool_bin returns string
+        soname 'libudf_hash.so'",
+    "create or replace function shake128 returns string
+        soname 'libudf_hash.so'",
+    "create or replace function shake128_bin returns string
+        soname 'libudf_hash.so'",
+    "create or replace function shake256 returns string
+        soname 'libudf_hash.so'",
+    "create or replace function shake256_bin returns string
+        soname 'libudf_hash.so'",
+    "create or replace function tuplehash128 returns string
+        soname 'libudf_hash.so'",
+    "create or replace function tuplehash128_bin returns string
+        soname 'libudf_hash.so'",
+    "create or replace function tuplehash256 returns string
+        soname 'libudf_hash.so'",
+    "create or replace function tuplehash256_bin returns string
+        soname 'libudf_hash.so'",
+    "create or replace function kmac128 returns string
+        soname 'libudf_hash.so'",
+    "create or replace function kmac128_bin returns string
+        soname 'libudf_hash.so'",
+    "create or replace function kmac256 returns string
+        soname 'libudf_hash.so'",
+    "create or replace function kmac256_bin returns string
+        soname 'libudf_hash.so'",
+    "create or replace function cshake256 returns string
+        soname 'libudf_hash.so'",
+    "create or replace function cshake256_bin returns string
+        soname 'libudf_hash.so'",
+    "create or replace function k12 returns string
+        soname 'libudf_hash.so'",
+    "create or replace function k12_bin returns string
+        soname 'libudf_hash.so'",
     "create or replace function xxhash returns integer
         soname 'libudf_hash.so'",
     "create or replace function xxhash3 returns integer
@@ -78,15 +139,339 @@ const SETUP: &[&str] = &[
         soname 'libudf_hash.so'",
     "create or replace function xxhash64 returns integer
         soname 'libudf_hash.so'",
+    "create or replace aggregate function blake2b512_agg returns string
+        soname 'libudf_hash.so'",
+    "create or replace aggregate function blake2b512_agg_bin returns string
+        soname 'libudf_hash.so'",
+    "create or replace aggregate function blake2s256_agg returns string
+        soname 'libudf_hash.so'",
+    "create or replace aggregate function blake2s256_agg_bin returns string
+        soname 'libudf_hash.so'",
+    "create or replace aggregate function blake3_agg returns string
+        soname 'libudf_hash.so'",
+    "create or replace aggregate function blake3_agg_bin returns string
+        soname 'libudf_hash.so'",
+    "create or replace aggregate function md5_u_agg returns string
+        soname 'libudf_hash.so'",
+    "create or replace aggregate function md5_u_agg_bin returns string
+        soname 'libudf_hash.so'",
+    "create or replace aggregate function sha1_u_agg returns string
+        soname 'libudf_hash.so'",
+    "create or replace aggregate function sha1_u_agg_bin returns string
+        soname 'libudf_hash.so'",
+    "create or replace aggregate function sha224_agg returns string
+        soname 'libudf_hash.so'",
+    "create or replace aggregate function sha224_agg_bin returns string
+        soname 'libudf_hash.so'",
+    "create or replace aggregate function sha256_agg returns string
+        soname 'libudf_hash.so'",
+    "create or replace aggregate function sha256_agg_bin returns string
+        soname 'libudf_hash.so'",
+    "create or replace aggregate function sha384_agg returns string
+        soname 'libudf_hash.so'",
+    "create or replace aggregate function sha384_agg_bin returns string
+        soname 'libudf_hash.so'",
+    "create or replace aggregate function sha512_agg returns string
+        soname 'libudf_hash.so'",
+    "create or replace aggregate function sha512_agg_bin returns string
+        soname 'libudf_hash.so'",
+    "create or replace aggregate function keccak224_agg returns string
+        soname 'libudf_hash.so'",
+    "create or replace aggregate function keccak224_agg_bin returns string
+        soname 'libudf_hash.so'",
+    "create or replace aggregate function keccak256_agg returns string
+        soname 'libudf_hash.so'",
+    "create or replace aggregate function keccak256_agg_bin returns string
+        soname 'libudf_hash.so'",
+    "create or replace aggregate function sha3_224_agg returns string
+        soname 'libudf_hash.so'",
+    "create or replace aggregate function sha3_224_agg_bin returns string
+        soname 'libudf_hash.so'",
+    "create or replace aggregate function sha3_256_agg returns string
+        soname 'libudf_hash.so'",
+    "create or replace aggregate function sha3_256_agg_bin returns string
+        soname 'libudf_hash.so'",
+    "create or replace aggregate function sha3_384_agg returns string
+        soname 'libudf_hash.so'",
+    "create or replace aggregate function sha3_384_agg_bin returns string
+        soname 'libudf_hash.so'",
+    "create or replace aggregate function sha3_512_agg returns string
+        soname 'libudf_hash.so'",
+    "create or replace aggregate function sha3_512_agg_bin returns string
+        soname 'libudf_hash.so'",
+    "create or replace aggregate function xxhash_agg returns integer
+        soname 'libudf_hash.so'",
+    "create or replace aggregate function xxhash3_agg returns integer
+        soname 'libudf_hash.so'",
+    "create or replace aggregate function xxhash32_agg returns integer
+        soname 'libudf_hash.so'",
+    "create or replace aggregate function xxhash64_agg returns integer
+        soname 'libudf_hash.so'",
+    "create or replace aggregate function blake3_set_agg returns string
+        soname 'libudf_hash.so'",
+    "create or replace aggregate function blake3_set_agg_bin returns string
+        soname 'libudf_hash.so'",
+    "create or replace aggregate function xxhash_set_agg returns integer
+        soname 'libudf_hash.so'",
+    "create or replace aggregate function xxhash64_set_agg returns integer
+        soname 'libudf_hash.so'",
+    "create or replace function hmac_blake2b512 returns string
+        soname 'libudf_hash.so'",
+    "create or replace function hmac_blake2b512_bin returns string
+        soname 'libudf_hash.so'",
+    "create or replace function hmac_blake2s256 returns string
+        soname 'libudf_hash.so'",
+    "create or replace function hmac_blake2s256_bin returns string
+        soname 'libudf_hash.so'",
+    "create or replace function hmac_sha1 returns string
+        soname 'libudf_hash.so'",
+    "create or replace function hmac_sha1_bin returns string
+        soname 'libudf_hash.so'",
+    "create or replace function hmac_md5 returns string
+        soname 'libudf_hash.so'",
+    "create or replace function hmac_md5_bin returns string
+        soname 'libudf_hash.so'",
+    "create or replace function hmac_sha224 returns string
+        soname 'libudf_hash.so'",
+    "create or replace function hmac_sha224_bin returns string
+        soname 'libudf_hash.so'",
+    "create or replace function hmac_sha256 returns string
+        soname 'libudf_hash.so'",
+    "create or replace function hmac_sha256_bin returns string
+        soname 'libudf_hash.so'",
+    "create or replace function hmac_sha384 returns string
+        soname 'libudf_hash.so'",
+    "create or replace function hmac_sha384_bin returns string
+        soname 'libudf_hash.so'",
+    "create or replace function hmac_sha512 returns string
+        soname 'libudf_hash.so'",
+    "create or replace function hmac_sha512_bin returns string
+        soname 'libudf_hash.so'",
+    "create or replace function hmac_keccak224 returns string
+        soname 'libudf_hash.so'",
+    "create or replace function hmac_keccak224_bin returns string
+        soname 'libudf_hash.so'",
+    "create or replace function hmac_keccak256 returns string
+        soname 'libudf_hash.so'",
+    "create or replace function hmac_keccak256_bin returns string
+        soname 'libudf_hash.so'",
+    "create or replace function hmac_sha3_224 returns string
+        soname 'libudf_hash.so'",
+    "create or replace function hmac_sha3_224_bin returns string
+        soname 'libudf_hash.so'",
+    "create or replace function hmac_sha3_256 returns string
+        soname 'libudf_hash.so'",
+    "create or replace function hmac_sha3_256_bin returns string
+        soname 'libudf_hash.so'",
+    "create or replace function hmac_sha3_384 returns string
+        soname 'libudf_hash.so'",
+    "create or replace function hmac_sha3_384_bin returns string
+        soname 'libudf_hash.so'",
+    "create or replace function hmac_sha3_512 returns string
+        soname 'libudf_hash.so'",
+    "create or replace function hmac_sha3_512_bin returns string
+        soname 'libudf_hash.so'",
+    "create or replace function blake3_keyed returns string
+        soname 'libudf_hash.so'",
+    "create or replace function blake3_keyed_bin returns string
+        soname 'libudf_hash.so'",
+    "create or replace function blake3_keyed_thd returns string
+        soname 'libudf_hash.so'",
+    "create or replace function blake3_keyed_thd_bin returns string
+        soname 'libudf_hash.so'",
+    "create or replace function blake3_derive_key returns string
+        soname 'libudf_hash.so'",
+    "create or replace function blake3_derive_key_bin returns string
+        soname 'libudf_hash.so'",
+    "create or replace function blake3_derive_key_thd returns string
+        soname 'libudf_hash.so'",
+    "create or replace function blake3_derive_key_thd_bin returns string
+        soname 'libudf_hash.so'",
+    "create or replace function blake3_xof returns string
+        soname 'libudf_hash.so'",
+    "create or replace function blake3_xof_bin returns string
+        soname 'libudf_hash.so'",
+    "create or replace function blake3_xof_thd returns string
+        soname 'libudf_hash.so'",
+    "create or replace function blake3_xof_thd_bin returns string
+        soname 'libudf_hash.so'",
+    "create or replace function blake2b512_tuple returns string
+        soname 'libudf_hash.so'",
+    "create or replace function blake2b512_tuple_bin returns string
+        soname 'libudf_hash.so'",
+    "create or replace function blake2s256_tuple returns string
+        soname 'libudf_hash.so'",
+    "create or replace function blake2s256_tuple_bin returns string
+        soname 'libudf_hash.so'",
+    "create or replace function blake3_tuple returns string
+        soname 'libudf_hash.so'",
+    "create or replace function blake3_tuple_bin returns string
+        soname 'libudf_hash.so'",
+    "create or replace function md5_u_tuple returns string
+        soname 'libudf_hash.so'",
+    "create or replace function md5_u_tuple_bin returns string
+        soname 'libudf_hash.so'",
+    "create or replace function sha1_u_tuple returns string
+        soname 'libudf_hash.so'",
+    "create or replace function sha1_u_tuple_bin returns string
+        soname 'libudf_hash.so'",
+    "create or replace function sha224_tuple returns string
+        soname 'libudf_hash.so'",
+    "create or replace function sha224_tuple_bin returns string
+        soname 'libudf_hash.so'",
+    "create or replace function sha256_tuple returns string
+        soname 'libudf_hash.so'",
+    "create or replace function sha256_tuple_bin returns string
+        soname 'libudf_hash.so'",
+    "create or replace function sha384_tuple returns string
+        soname 'libudf_hash.so'",
+    "create or replace function sha384_tuple_bin returns string
+        soname 'libudf_hash.so'",
+    "create or replace function sha512_tuple returns string
+        soname 'libudf_hash.so'",
+    "create or replace function sha512_tuple_bin returns string
+        soname 'libudf_hash.so'",
+    "create or replace function keccak224_tuple returns string
+        soname 'libudf_hash.so'",
+    "create or replace function keccak224_tuple_bin returns string
+        soname 'libudf_hash.so'",
+    "create or replace function keccak256_tuple returns string
+        soname 'libudf_hash.so'",
+    "create or replace function keccak256_tuple_bin returns string
+        soname 'libudf_hash.so'",
+    "create or replace function sha3_224_tuple returns string
+        soname 'libudf_hash.so'",
+    "create or replace function sha3_224_tuple_bin returns string
+        soname 'libudf_hash.so'",
+    "create or replace function sha3_256_tuple returns string
+        soname 'libudf_hash.so'",
+    "create or replace function sha3_256_tuple_bin returns string
+        soname 'libudf_hash.so'",
+    "create or replace function sha3_384_tuple returns string
+        soname 'libudf_hash.so'",
+    "create or replace function sha3_384_tuple_bin returns string
+        soname 'libudf_hash.so'",
+    "create or replace function sha3_512_tuple returns string
+        soname 'libudf_hash.so'",
+    "create or replace function sha3_512_tuple_bin returns string
+        soname 'libudf_hash.so'",
+    "create or replace function hash_encode returns string
+        soname 'libudf_hash.so'",
+    "create or replace function hash returns string
+        soname 'libudf_hash.so'",
+    "create or replace function hash_bin returns string
+        soname 'libudf_hash.so'",
+    "create or replace function hash_canonical returns string
+        soname 'libudf_hash.so'",
+    "create or replace function hash_canonical_bin returns string
+        soname 'libudf_hash.so'",
+    "create or replace function xxhash32_seed returns integer
+        soname 'libudf_hash.so'",
+    "create or replace function xxhash64_seed returns integer
+        soname 'libudf_hash.so'",
+    "create or replace function xxhash3_seed returns integer
+        soname 'libudf_hash.so'",
+    "create or replace function xxhash3_secret returns integer
+        soname 'libudf_hash.so'",
+    "create or replace function xxhash128 returns string
+        soname 'libudf_hash.so'",
+    "create or replace function xxhash128_bin returns string
+        soname 'libudf_hash.so'",
+    "create or replace function xxhash3_u returns decimal
+        soname 'libudf_hash.so'",
+    "create or replace function xxhash64_u returns decimal
+        soname 'libudf_hash.so'",
+    "create or replace function xxhash_u returns decimal
+        soname 'libudf_hash.so'",
+    "create or replace function argon2id_hash returns string
+        soname 'libudf_hash.so'",
+    "create or replace function bcrypt_hash returns string
+        soname 'libudf_hash.so'",
+    "create or replace function scrypt_hash returns string
+        soname 'libudf_hash.so'",
+    "create or replace function pbkdf2_sha256 returns string
+        soname 'libudf_hash.so'",
+    "create or replace function password_verify returns integer
+        soname 'libudf_hash.so'",
+    "create or replace function crc32c returns integer
+        soname 'libudf_hash.so'",
+    "create or replace function crc64_nvme returns integer
+        soname 'libudf_hash.so'",
+    "create or replace function fnv1a_32 returns integer
+        soname 'libudf_hash.so'",
+    "create or replace function fnv1a_64 returns integer
+        soname 'libudf_hash.so'",
+    "create or replace function murmur3_32 returns integer
+        soname 'libudf_hash.so'",
+    "create or replace function murmur3_128 returns string
+        soname 'libudf_hash.so'",
+    "create or replace function murmur3_128_bin returns string
+        soname 'libudf_hash.so'",
+    "create or replace function siphash24 returns integer
+        soname 'libudf_hash.so'",
+    "create or replace function highwayhash64 returns integer
+        soname 'libudf_hash.so'",
+    "create or replace function jump_hash returns integer
+        soname 'libudf_hash.so'",
+    "create or replace function rendezvous_hash returns string
+        soname 'libudf_hash.so'",
+    "create or replace function hash_bucket returns integer
+        soname 'libudf_hash.so'",
+    "create or replace function hash_to_unit returns real
+        soname 'libudf_hash.so'",
+    "create or replace aggregate function hll_agg returns string
+        soname 'libudf_hash.so'",
+    "create or replace aggregate function hll_merge_agg returns string
+        soname 'libudf_hash.so'",
+    "create or replace function hll_count returns integer
+        soname 'libudf_hash.so'",
+    "create or replace aggregate function bloom_agg returns string
+        soname 'libudf_hash.so'",
+    "create or replace aggregate function bloom_merge_agg returns string
+        soname 'libudf_hash.so'",
+    "create or replace function bloom_contains returns integer
+        soname 'libudf_hash.so'",
+    "create or replace aggregate function merkle_root_agg returns string
+        soname 'libudf_hash.so'",
+    "create or replace aggregate function merkle_root_agg_bin returns string
+        soname 'libudf_hash.so'",
+    "create or replace aggregate function merkle_root_blake3_agg returns string
+        soname 'libudf_hash.so'",
+    "create or replace aggregate function merkle_root_blake3_agg_bin returns string
+        soname 'libudf_hash.so'",
+    "create or replace function merkle_leaf_hash returns string
+        soname 'libudf_hash.so'",
+    "create or replace function merkle_leaf_hash_bin returns string
+        soname 'libudf_hash.so'",
+    "create or replace function merkle_leaf_blake3 returns string
+        soname 'libudf_hash.so'",
+    "create or replace function merkle_leaf_blake3_bin returns string
+        soname 'libudf_hash.so'",
+    "create or replace function minhash returns string
+        soname 'libudf_hash.so'",
+    "create or replace function minhash_similarity returns real
+        soname 'libudf_hash.so'",
+    "create or replace function simhash64 returns integer
+        soname 'libudf_hash.so'",
+    "create or replace function hamming_distance returns integer
+        soname 'libudf_hash.so'",
 ];
 
 const TEST: &str = "Hello, world!";
 const PARTS: (&str, &str, &str) = ("Hello, ", "world", "!");
+/// Turn `PARTS` into rows, for testing aggregates
+const PARTS_ROWS: &str = "(select ? as v union all select ? union all select ?) as t";
+const PARTS_REV: (&str, &str, &str) = ("!", "world", "Hello, ");
+const HMAC_KEY: &str = "key";
 
 const RESULT_BLAKE2B512: &str = "A2764D133A16816B5847A737A786F2ECE4C148095C5FAA73E24B4CC5D666C3E4\
                                  5EC271504E14DC6127DDFCE4E144FB23B91A6F7B04B53D695502290722953B0F";
 const RESULT_BLAKE2S256: &str = "30D8777F0E178582EC8CD2FCDC18AF57C828EE2F89E978DF52C8E7AF078BD5CF";
 const RESULT_BLAKE3: &str = "EDE5C0B10F2EC4979C69B52F61E42FF5B413519CE09BE0F14D098DCFE5F6F98D";
+/// RFC 6962 root of a tree with the rows of `PARTS` as leaves
+const RESULT_MERKLE_ROOT: &str = "5600B50752DCF6DEF1568E8262EBAB2E7A0005D4939B0787958858EEA0CE7036";
+/// RFC 6962 leaf hash of `TEST`
+const RESULT_MERKLE_LEAF: &str = "5B46949AADBEDB396264FE3EED80CFE647906F1BAB038E49AB9C1EBB85CFFF15";
 const RESULT_MD5: &str = "6CD3556DEB0DA54BCA060B4C39479839";
 const RESULT_SHA1: &str = "943A702D06F34599AEE1F8DA8EF9F7296031D699";
 const RESULT_SHA224: &str = "8552D8B7A7DC5476CB9E25DEE69A8091290764B7F2A64FE6E78E9568";
@@ -103,9 +488,71 @@ const RESULT_SHA3_384: &str = "6BA9EA268965916F5937228DDE678C202F9FE756A87D8B1B7
                                362869583A45901FD1A27289D72FC0E3FF48B1B78827D3A";
 const RESULT_SHA3_512: &str = "8E47F1185FFD014D238FABD02A1A32DEFE698CBF38C037A90E3C0A0A32370FB5\
                                2CBD641250508502295FCABCBF676C09470B27443868C8E5F70E26DC337288AF";
+
+// HMACs of `TEST` with key `HMAC_KEY`
+const RESULT_HMAC_BLAKE2B512: &str = "D553AE09B2903BB47E1A290EDE4EA6D7522370F7D15140E9CCA02EAF63876D60\
+                                      E4A872FDCC3820374993DDD26821AA24B1BC1A5BB94AF73736E3D1E5ACCC7233";
+const RESULT_HMAC_BLAKE2S256: &str =
+    "BAC6C9BB9A3C3B8A4370E80314DA719868DCE941FF12AF461BF1BA0001CAF622";
+const RESULT_HMAC_MD5: &str = "8013ADBD3F9EFF856800E8D3A7077CEF";
+const RESULT_HMAC_SHA1: &str = "FED44B832F010916C6872A4122234E57638A2DFD";
+const RESULT_HMAC_SHA224: &str = "1D4378FF2F1AE944BFA70ED746E92E79EBD5E54E643A51CF72A05BB7";
+const RESULT_HMAC_SHA256: &str = "7579F2EF9632FA31AB440AB7FAB06CE4511E7DF233773C88302818B3B184595B";
+const RESULT_HMAC_SHA384: &str = "559F5EF48C4A9B181399B77581583EAC94B96D65BEE7693E\
+                                  711D491D549E5F9EAAE5B56F69525DAE2D0D581CA54BEB5C";
+const RESULT_HMAC_SHA512: &str = "4C5947027B629BAB8571613B4DC9D39AFE9C80D3E8F1B0BB569E25135B0C4FD4\
+                                  547949B2A35411425D6DD4EDDB6EEA55012DF94D0FD6A0618CF2E5A7E19879D1";
+const RESULT_HMAC_SHA3_224: &str = "1456FF24C2B20051D27A76C509C5BDBD4175A85D7E6012BBC7D8A9BB";
+const RESULT_HMAC_SHA3_256: &str =
+    "58DF077D3E3BF1E46AF3D7F7742414032CA78D7E2CCFD39E95F2D0E4011F8B49";
+const RESULT_HMAC_SHA3_384: &str = "10D7AE87C5774D8AA859162FA9182D173EEC54A7C94721C2\
+                                    49D1840C89A8FC427FB43E4D448F6A290E66FD992B240470";
+const RESULT_HMAC_SHA3_512: &str = "FB8BED0767603FD9EB1404A49A4B0560C3107C08C66AF7A528C4970B6F1FEC55\
+                                    93E6F2748145DBAAB54FAEF417B40A0B09A1744A2207D1400BE86E977211B07B";
+// Blake3 modes
+const BLAKE3_KEY: &str = "whats the Elvish word for friend";
+const BLAKE3_CONTEXT: &str = "udf-hash test context";
+const RESULT_BLAKE3_KEYED: &str =
+    "536591A1FB7D7939143BBED29B64CE861D73D07C80C50FF98B9CAEC3C54CC384";
+const RESULT_BLAKE3_DERIVE_KEY: &str =
+    "AF0F56E594A82CD27CDF6B6FDFBE780297D97A97C2D8FE61706F2E0871CE118D";
+const RESULT_BLAKE3_XOF_64: &str = "EDE5C0B10F2EC4979C69B52F61E42FF5B413519CE09BE0F14D098DCFE5F6F98D\
+                                    7D46337812F87327AD7108FBAA955F009AFA495CA8C05C33EB050002AC9B99BC";
+
+// `sha256_tuple` of `PARTS` and of `TEST`
+const RESULT_SHA256_TUPLE_PARTS: &str =
+    "1B961656C9A1213974CC79755CE74CBD4021F66126E675F54CF236DD89B275BD";
+const RESULT_SHA256_TUPLE: &str =
+    "5F7AE95CF6114F83CBDCEE764E962BF30D6FAE30685CF931B2F1F4F93DF9D5A8";
+
+const RESULT_SHA512_224: &str = "32620068B859669B45B31008E08B7384649AD2CA3F5163A3A71E5745";
+const RESULT_SHA512_256: &str = "330C723F25267587DB0B9F493463E017011239169CB57A6DB216C63774367115";
+const RESULT_RIPEMD160: &str = "58262D1FBDBE4530D8865D3518C6D6E41002610F";
+const RESULT_SM3: &str = "E3BCA101B496880C3653DAD85861D0E784B00A8C18F7574472D156060E9096BF";
+const RESULT_STREEBOG256: &str = "CCB6FAE3553C101715DA535328DE718F6F6E412DB8611A38025C510AC8F85AEB";
+const RESULT_STREEBOG512: &str = "A83352D35DC8F07CA8048E6752415E5E991527E29415ADE0EAAD6E48D67BF37B\
+                                  60DFD7BB4475CBCBE297ED016128391C312DFE3A00E0A9BD0E497389C888EEDC";
+const RESULT_WHIRLPOOL: &str = "A1A8703BE5312B139B42EB331AA800CCACA0C34D58C6988E44F45489CFB16BEB\
+                                4B6BF0CE20BE1DB22A10B0E4BB680480A3D2429E6C483085453C098B65852495";
+const RESULT_SHAKE128_20: &str = "B5FFD113FA127F4D9C7E483CB52264ED413554EF";
+// SHA-256 then RIPEMD-160, as used for Bitcoin addresses
+const RESULT_HASH160: &str = "8D159F1C4F99D8ED858F7832310DB31CB91E0745";
+const RESULT_KMAC256_32: &str = "E2170B2734D2603203E1D7632032B81B7033D47DE513067CEEB17B32A8A0A52F";
+const RESULT_TUPLEHASH128_PARTS: &str =
+    "F3DB9E27026FC16BED0B0EA8B919599CDD3D7D016C528EAAAD7E48C0CF85423C";
+const RESULT_K12_32: &str = "2A7ECCAA09FF7E30CB1413BDA28DAD7F90759F22FC63535369BF17595B1166AF";
+
 const RESULT_XXHASH3: i64 = 0xf3c34bf11915e869_u64 as i64;
 const RESULT_XXHASH32: u32 = 0x31b7405d;
 const RESULT_XXHASH64: i64 = 0xf58336a78b6f9476_u64 as i64;
+const RESULT_XXHASH128: &str = "ADE2B2886B80787BBB5CA743B534F2FA";
+
+// Seeded with 42
+const RESULT_XXHASH3_SEED: i64 = 0xd90b9cc9111992ea_u64 as i64;
+const RESULT_XXHASH32_SEED: u32 = 0x01d7440c;
+const RESULT_XXHASH64_SEED: i64 = 0x7a66070039ea8f53_u64 as i64;
+// Secret of `repeat('0123456789abcdef', 12)`
+const RESULT_XXHASH3_SECRET: i64 = 0x88098ba30a581b97_u64 as i64;
 
 macro_rules! make_hash_test {
     ($sql_fn:ident, $expected:ident) => {
@@ -141,6 +588,80 @@ macro_rules! make_hash_test {
     };
 }
 
+// Aggregate hashes should match hashing the concatenated rows
+macro_rules! make_agg_hash_test {
+    ($sql_fn:ident, $expected:ident) => {
+        #[test]
+        fn $sql_fn() {
+            let conn = &mut get_db_connection(SETUP);
+            let fn_name = stringify!($sql_fn);
+
+            let res: String = conn
+                .exec_first(&format!("select {fn_name}(v) from {PARTS_ROWS}"), PARTS)
+                .unwrap()
+                .unwrap();
+
+            assert_eq!(res, $expected);
+
+            let res: String = conn
+                .exec_first(
+                    &format!("select hex({fn_name}_bin(v)) from {PARTS_ROWS}"),
+                    PARTS,
+                )
+                .unwrap()
+                .unwrap();
+
+            assert_eq!(res, $expected);
+        }
+    };
+}
+
+// HMACs take a key then the data to hash
+macro_rules! make_hmac_test {
+    ($sql_fn:ident, $expected:ident) => {
+        #[test]
+        fn $sql_fn() {
+            let conn = &mut get_db_connection(SETUP);
+            let fn_name = stringify!($sql_fn);
+
+            let res: String = conn
+                .exec_first(&format!("select {fn_name}(?, ?)"), (HMAC_KEY, TEST))
+                .unwrap()
+                .unwrap();
+
+            assert_eq!(res, $expected);
+
+            let res: String = conn
+                .exec_first(
+                    &format!("select {fn_name}(?, ?, ?, ?)"),
+                    (HMAC_KEY, PARTS.0, PARTS.1, PARTS.2),
+                )
+                .unwrap()
+                .unwrap();
+
+            assert_eq!(res, $expected);
+
+            let res: String = conn
+                .exec_first(
+                    &format!("select hex({fn_name}_bin(?, ?))"),
+                    (HMAC_KEY, TEST),
+                )
+                .unwrap()
+                .unwrap();
+
+            assert_eq!(res, $expected);
+
+            // A null key gives a null result
+            let res: Option<String> = conn
+                .exec_first(&format!("select {fn_name}(NULL, ?)"), (TEST,))
+                .unwrap()
+                .unwrap();
+
+            assert_eq!(res, None);
+        }
+    };
+}
+
 make_hash_test!(blake2b512, RESULT_BLAKE2B512);
 make_hash_test!(blake2s256, RESULT_BLAKE2S256);
 make_hash_test!(blake3, RESULT_BLAKE3);
@@ -157,6 +678,42 @@ make_hash_test!(sha3_224, RESULT_SHA3_224);
 make_hash_test!(sha3_256, RESULT_SHA3_256);
 make_hash_test!(sha3_384, RESULT_SHA3_384);
 make_hash_test!(sha3_512, RESULT_SHA3_512);
+make_hash_test!(sha512_224, RESULT_SHA512_224);
+make_hash_test!(sha512_256, RESULT_SHA512_256);
+make_hash_test!(ripemd160, RESULT_RIPEMD160);
+make_hash_test!(sm3, RESULT_SM3);
+make_hash_test!(streebog256, RESULT_STREEBOG256);
+make_hash_test!(streebog512, RESULT_STREEBOG512);
+make_hash_test!(whirlpool, RESULT_WHIRLPOOL);
+
+make_agg_hash_test!(blake2b512_agg, RESULT_BLAKE2B512);
+make_agg_hash_test!(blake2s256_agg, RESULT_BLAKE2S256);
+make_agg_hash_test!(blake3_agg, RESULT_BLAKE3);
+make_agg_hash_test!(md5_u_agg, RESULT_MD5);
+make_agg_hash_test!(sha1_u_agg, RESULT_SHA1);
+make_agg_hash_test!(sha224_agg, RESULT_SHA224);
+make_agg_hash_test!(sha256_agg, RESULT_SHA256);
+make_agg_hash_test!(sha384_agg, RESULT_SHA384);
+make_agg_hash_test!(sha512_agg, RESULT_SHA512);
+make_agg_hash_test!(keccak224_agg, RESULT_KECCAK224);
+make_agg_hash_test!(keccak256_agg, RESULT_KECCAK256);
+make_agg_hash_test!(sha3_224_agg, RESULT_SHA3_224);
+make_agg_hash_test!(sha3_256_agg, RESULT_SHA3_256);
+make_agg_hash_test!(sha3_384_agg, RESULT_SHA3_384);
+make_agg_hash_test!(sha3_512_agg, RESULT_SHA3_512);
+
+make_hmac_test!(hmac_blake2b512, RESULT_HMAC_BLAKE2B512);
+make_hmac_test!(hmac_blake2s256, RESULT_HMAC_BLAKE2S256);
+make_hmac_test!(hmac_md5, RESULT_HMAC_MD5);
+make_hmac_test!(hmac_sha1, RESULT_HMAC_SHA1);
+make_hmac_test!(hmac_sha224, RESULT_HMAC_SHA224);
+make_hmac_test!(hmac_sha256, RESULT_HMAC_SHA256);
+make_hmac_test!(hmac_sha384, RESULT_HMAC_SHA384);
+make_hmac_test!(hmac_sha512, RESULT_HMAC_SHA512);
+make_hmac_test!(hmac_sha3_224, RESULT_HMAC_SHA3_224);
+make_hmac_test!(hmac_sha3_256, RESULT_HMAC_SHA3_256);
+make_hmac_test!(hmac_sha3_384, RESULT_HMAC_SHA3_384);
+make_hmac_test!(hmac_sha3_512, RESULT_HMAC_SHA3_512);
 
 // xxhash uses integers so we can't use our macro
 
@@ -231,3 +788,994 @@ fn test_xxhash64() {
 
     assert_eq!(res, RESULT_XXHASH64);
 }
+
+#[test]
+fn test_xxhash_agg() {
+    let conn = &mut get_db_connection(SETUP);
+
+    let res: i64 = conn
+        .exec_first(&format!("select xxhash3_agg(v) from {PARTS_ROWS}"), PARTS)
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(res, RESULT_XXHASH3);
+
+    let res: u32 = conn
+        .exec_first(&format!("select xxhash32_agg(v) from {PARTS_ROWS}"), PARTS)
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(res, RESULT_XXHASH32);
+
+    let res: i64 = conn
+        .exec_first(&format!("select xxhash64_agg(v) from {PARTS_ROWS}"), PARTS)
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(res, RESULT_XXHASH64);
+
+    let res: i64 = conn
+        .exec_first(&format!("select xxhash_agg(v) from {PARTS_ROWS}"), PARTS)
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(res, RESULT_XXHASH64);
+}
+
+#[test]
+fn test_set_agg() {
+    let conn = &mut get_db_connection(SETUP);
+
+    for fn_name in ["blake3_set_agg", "xxhash_set_agg", "xxhash64_set_agg"] {
+        let res: Value = conn
+            .exec_first(&format!("select {fn_name}(v) from {PARTS_ROWS}"), PARTS)
+            .unwrap()
+            .unwrap();
+        let res_rev: Value = conn
+            .exec_first(&format!("select {fn_name}(v) from {PARTS_ROWS}"), PARTS_REV)
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(res, res_rev, "{fn_name}");
+
+        // Results should differ from a group with different content
+        let res_other: Value = conn
+            .exec_first(
+                &format!("select {fn_name}(v) from {PARTS_ROWS}"),
+                ("Hello, ", "world", "?"),
+            )
+            .unwrap()
+            .unwrap();
+
+        assert_ne!(res, res_other, "{fn_name}");
+    }
+
+    let res: String = conn
+        .exec_first(
+            &format!("select hex(blake3_set_agg_bin(v)) from {PARTS_ROWS}"),
+            PARTS_REV,
+        )
+        .unwrap()
+        .unwrap();
+    let expected: String = conn
+        .exec_first(
+            &format!("select blake3_set_agg(v) from {PARTS_ROWS}"),
+            PARTS,
+        )
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(res, expected);
+}
+
+#[test]
+fn test_blake3_modes() {
+    let conn = &mut get_db_connection(SETUP);
+
+    for suffix in ["", "_thd"] {
+        let res: String = conn
+            .exec_first(
+                &format!("select blake3_keyed{suffix}(?, ?, ?, ?)"),
+                (BLAKE3_KEY, PARTS.0, PARTS.1, PARTS.2),
+            )
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(res, RESULT_BLAKE3_KEYED);
+
+        let res: String = conn
+            .exec_first(
+                &format!("select hex(blake3_keyed{suffix}_bin(?, ?))"),
+                (BLAKE3_KEY, TEST),
+            )
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(res, RESULT_BLAKE3_KEYED);
+
+        let res: String = conn
+            .exec_first(
+                &format!("select blake3_derive_key{suffix}(?, ?)"),
+                (BLAKE3_CONTEXT, TEST),
+            )
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(res, RESULT_BLAKE3_DERIVE_KEY);
+
+        let res: String = conn
+            .exec_first(
+                &format!("select hex(blake3_derive_key{suffix}_bin(?, ?))"),
+                (BLAKE3_CONTEXT, TEST),
+            )
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(res, RESULT_BLAKE3_DERIVE_KEY);
+
+        let res: String = conn
+            .exec_first(&format!("select blake3_xof{suffix}(64, ?)"), (TEST,))
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(res, RESULT_BLAKE3_XOF_64);
+
+        // Shorter output is a prefix of longer output
+        let res: String = conn
+            .exec_first(
+                &format!("select hex(blake3_xof{suffix}_bin(32, ?))"),
+                (TEST,),
+            )
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(res, RESULT_BLAKE3);
+    }
+
+    // Keys must be 32 bytes
+    let res = conn.exec_drop("select blake3_keyed('short key', 'data')", ());
+    assert!(res.is_err());
+
+    // Contexts must be text
+    let res = conn.exec_drop("select blake3_derive_key(unhex('FF'), 'data')", ());
+    assert!(res.is_err());
+}
+
+#[test]
+fn test_tuple() {
+    let conn = &mut get_db_connection(SETUP);
+
+    let res: String = conn
+        .exec_first("select sha256_tuple(?, ?, ?)", PARTS)
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(res, RESULT_SHA256_TUPLE_PARTS);
+
+    let res: String = conn
+        .exec_first("select hex(sha256_tuple_bin(?))", (TEST,))
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(res, RESULT_SHA256_TUPLE);
+
+    for fn_name in [
+        "blake2b512",
+        "blake2s256",
+        "blake3",
+        "md5_u",
+        "sha1_u",
+        "sha224",
+        "sha256",
+        "sha384",
+        "sha512",
+        "keccak224",
+        "keccak256",
+        "sha3_224",
+        "sha3_256",
+        "sha3_384",
+        "sha3_512",
+    ] {
+        // Argument boundaries matter
+        let res: (String, String) = conn
+            .query_first(format!(
+                "select {fn_name}_tuple('ab', 'c'), {fn_name}_tuple('a', 'bc')"
+            ))
+            .unwrap()
+            .unwrap();
+
+        assert_ne!(res.0, res.1, "{fn_name}");
+
+        // NULL is distinct from an empty string
+        let res: (String, String) = conn
+            .query_first(format!(
+                "select {fn_name}_tuple('', 'a'), {fn_name}_tuple(NULL, 'a')"
+            ))
+            .unwrap()
+            .unwrap();
+
+        assert_ne!(res.0, res.1, "{fn_name}");
+
+        let res: (String, String) = conn
+            .exec_first(
+                &format!("select {fn_name}_tuple(?), hex({fn_name}_tuple_bin(?))"),
+                (TEST, TEST),
+            )
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(res.0, res.1, "{fn_name}");
+    }
+}
+
+#[test]
+fn test_hash_encode() {
+    let conn = &mut get_db_connection(SETUP);
+
+    for (encoding, expected) in [
+        ("hex", RESULT_SHA256.to_ascii_lowercase()),
+        ("HEXUPPER", RESULT_SHA256.to_owned()),
+        (
+            "b64",
+            "MV9b23bQeMQ7isAGTkoBZGErH853yGk0W/yUx1iU7dM=".to_owned(),
+        ),
+        (
+            "base64url",
+            "MV9b23bQeMQ7isAGTkoBZGErH853yGk0W_yUx1iU7dM".to_owned(),
+        ),
+        (
+            "base32",
+            "GFPVXW3W2B4MIO4KYADE4SQBMRQSWH6OO7EGSNC37SKMOWEU5XJQ====".to_owned(),
+        ),
+        (
+            "crockford",
+            "65FNQPVPT1WC8EWAR034WJG1CHGJP7YEEZ46JD2VZJACEP4MXQ9G".to_owned(),
+        ),
+    ] {
+        let res: String = conn
+            .exec_first("select hash_encode(sha256_bin(?), ?)", (TEST, encoding))
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(res, expected, "{encoding}");
+    }
+
+    // Encoding may vary per row
+    let res: Option<String> = conn
+        .query_first("select hash_encode('abc', if(rand() < 2, 'nope', 'hex'))")
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(res, None);
+
+    // Output is longer than the input, and must not be truncated
+    let res: (i64, i64) = conn
+        .query_first(
+            "select length(hash_encode(repeat('a', 100), 'hex')),
+                length(hash_encode(repeat('a', 100), if(rand() < 2, 'base32', 'hex')))",
+        )
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(res, (200, 160));
+
+    // Constant encodings are validated up front
+    let res = conn.exec_drop("select hash_encode('abc', 'base58')", ());
+    assert!(res.is_err());
+}
+
+#[test]
+fn test_hash_dispatch() {
+    let conn = &mut get_db_connection(SETUP);
+
+    let res: String = conn
+        .exec_first("select hash('sha3_256', ?)", (TEST,))
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(res, RESULT_SHA3_256);
+
+    let res: String = conn
+        .exec_first("select hex(hash_bin('BLAKE3', ?))", (TEST,))
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(res, RESULT_BLAKE3);
+
+    // xxhash output is the big endian digest
+    let res: String = conn
+        .exec_first("select hash('xxhash64', ?)", (TEST,))
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(res, format!("{:016X}", RESULT_XXHASH64 as u64));
+
+    // The algorithm may come from a column
+    let res: Vec<(String, Option<String>)> = conn
+        .exec(
+            "select algo, hash(algo, ?) from (
+                select 'md5' as algo union all
+                select 'sha3_256' union all
+                select 'md5' union all
+                select 'unknown'
+            ) as t",
+            (TEST,),
+        )
+        .unwrap();
+
+    assert_eq!(
+        res,
+        vec![
+            ("md5".to_owned(), Some(RESULT_MD5.to_owned())),
+            ("sha3_256".to_owned(), Some(RESULT_SHA3_256.to_owned())),
+            ("md5".to_owned(), Some(RESULT_MD5.to_owned())),
+            ("unknown".to_owned(), None),
+        ]
+    );
+
+    // Constant algorithms are validated up front
+    let res = conn.exec_drop("select hash('sha9000', 'data')", ());
+    assert!(res.is_err());
+}
+
+#[test]
+fn test_xxhash_seed() {
+    let conn = &mut get_db_connection(SETUP);
+
+    let res: (u32, i64, i64) = conn
+        .exec_first(
+            "select xxhash32_seed(42, ?), xxhash64_seed(42, ?), xxhash3_seed(42, ?)",
+            (TEST, TEST, TEST),
+        )
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(
+        res,
+        (
+            RESULT_XXHASH32_SEED,
+            RESULT_XXHASH64_SEED,
+            RESULT_XXHASH3_SEED
+        )
+    );
+
+    // A zero seed matches the unseeded functions
+    let res: (u32, i64, i64) = conn
+        .exec_first(
+            "select xxhash32_seed(0, ?, ?, ?), xxhash64_seed(0, ?, ?, ?), xxhash3_seed(0, ?, ?, ?)",
+            (
+                PARTS.0, PARTS.1, PARTS.2, PARTS.0, PARTS.1, PARTS.2, PARTS.0, PARTS.1, PARTS.2,
+            ),
+        )
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(res, (RESULT_XXHASH32, RESULT_XXHASH64, RESULT_XXHASH3));
+
+    let res: Option<i64> = conn
+        .exec_first("select xxhash64_seed(NULL, ?)", (TEST,))
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(res, None);
+}
+
+#[test]
+fn test_xxhash3_secret() {
+    let conn = &mut get_db_connection(SETUP);
+
+    let res: i64 = conn
+        .exec_first(
+            "select xxhash3_secret(repeat('0123456789abcdef', 12), ?)",
+            (TEST,),
+        )
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(res, RESULT_XXHASH3_SECRET);
+
+    let res = conn.exec_drop("select xxhash3_secret('short', 'data')", ());
+    assert!(res.is_err());
+}
+
+#[test]
+fn test_xxhash128() {
+    let conn = &mut get_db_connection(SETUP);
+
+    let res: String = conn
+        .exec_first("select xxhash128(?, ?, ?)", PARTS)
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(res, RESULT_XXHASH128);
+
+    let res: String = conn
+        .exec_first("select hex(xxhash128_bin(?))", (TEST,))
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(res, RESULT_XXHASH128);
+}
+
+#[test]
+fn test_xxhash_unsigned() {
+    let conn = &mut get_db_connection(SETUP);
+
+    let res: (u64, u64, u64) = conn
+        .exec_first(
+            "select xxhash3_u(?), xxhash64_u(?), xxhash_u(?)",
+            (TEST, TEST, TEST),
+        )
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(
+        res,
+        (
+            RESULT_XXHASH3 as u64,
+            RESULT_XXHASH64 as u64,
+            RESULT_XXHASH64 as u64
+        )
+    );
+
+    // Results compare numerically against unsigned values
+    let res: i64 = conn
+        .exec_first(
+            "select xxhash64_u(?, ?, ?) = cast(xxhash64(?) as unsigned)",
+            (PARTS.0, PARTS.1, PARTS.2, TEST),
+        )
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(res, 1);
+}
+
+#[test]
+fn test_password() {
+    let conn = &mut get_db_connection(SETUP);
+
+    // Use cheap parameters to keep the test fast
+    for (query, prefix) in [
+        (
+            "select argon2id_hash(?, 'm=64,t=1,p=1')",
+            "$argon2id$v=19$m=64,t=1,p=1$",
+        ),
+        ("select bcrypt_hash(?, 4)", "$2b$04$"),
+        ("select scrypt_hash(?, 'ln=4')", "$scrypt$ln=4,r=8,p=1$"),
+    ] {
+        let hash: String = conn.exec_first(query, ("hunter2",)).unwrap().unwrap();
+        assert!(hash.starts_with(prefix), "{hash}");
+
+        let res: (i64, i64) = conn
+            .exec_first(
+                "select password_verify('hunter2', ?), password_verify('hunter3', ?)",
+                (&hash, &hash),
+            )
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(res, (1, 0), "{hash}");
+    }
+
+    let hash: String = conn
+        .query_first("select pbkdf2_sha256('passwd', 'salt', 1, 32)")
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(
+        hash,
+        "$pbkdf2-sha256$i=1,l=32$c2FsdA$VawEblbjCJ/sFpHCJUS2BflBhSFt3gRl5oudV8INrLw"
+    );
+
+    let res: i64 = conn
+        .exec_first("select password_verify('passwd', ?)", (&hash,))
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(res, 1);
+
+    let res: Option<i64> = conn
+        .query_first("select password_verify('passwd', 'not a hash')")
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(res, Some(0));
+
+    let res = conn.exec_drop("select bcrypt_hash('pw', 50)", ());
+    assert!(res.is_err());
+
+    // Costs above the limits are refused rather than run
+    for query in [
+        "select argon2id_hash('pw', 'm=4194304,t=1,p=1')",
+        "select scrypt_hash('pw', 'ln=40')",
+        "select pbkdf2_sha256('pw', 'salt', 4294967295, 64)",
+    ] {
+        assert!(conn.exec_drop(query, ()).is_err(), "{query}");
+    }
+
+    let res: i64 = conn
+        .query_first(
+            "select password_verify('pw', \
+            '$scrypt$ln=40,r=8,p=1$c2FsdHNhbHQ$YWJjZGVmZ2hpamtsbW5vcHFyc3R1dnd4eXo')",
+        )
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(res, 0);
+}
+
+#[test]
+fn test_noncrypto() {
+    let conn = &mut get_db_connection(SETUP);
+
+    // Standard check values
+    let res: (i64, i64, i64, i64, i64) = conn
+        .query_first(
+            "select crc32c('123456789'), crc64_nvme('1234', '56789'), fnv1a_32('foobar'),
+            fnv1a_64('foo', 'bar'), murmur3_32('The quick brown fox jumps over the lazy dog')",
+        )
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(
+        res,
+        (
+            0xe3069283,
+            0xae8b14860a799888_u64 as i64,
+            0xbf9cf968,
+            0x85944171f73967e8_u64 as i64,
+            0x2e4ff723
+        )
+    );
+
+    let res: (String, String) = conn
+        .query_first("select murmur3_128(''), hex(murmur3_128_bin(''))")
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(res.0, "00000000000000000000000000000000");
+    assert_eq!(res.0, res.1);
+
+    // Guava's `Hashing.murmur3_128().hashString("hello", UTF_8)`, with both 64-bit
+    // halves little endian
+    let res: (String, String, i64) = conn
+        .query_first(
+            "select murmur3_128('hel', 'lo'), hex(murmur3_128_bin('hello')), murmur3_32('hello')",
+        )
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(res.0, "029BBD41B3A7D8CB191DAE486A901E5B");
+    assert_eq!(res.0, res.1);
+    assert_eq!(res.2, 0x248bfa47);
+
+    // Keyed hashes with the reference keys `00 01 02 ...` and an empty message
+    let res: (i64, i64) = conn
+        .query_first(
+            "select siphash24(unhex('000102030405060708090A0B0C0D0E0F')),
+            highwayhash64(unhex(
+                '000102030405060708090A0B0C0D0E0F101112131415161718191A1B1C1D1E1F'
+            ))",
+        )
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(res, (0x726fdb47dd0e0e31, 0x907a56de22c26e53_u64 as i64));
+
+    let res = conn.exec_drop("select siphash24('short key', 'data')", ());
+    assert!(res.is_err());
+}
+
+#[test]
+fn test_bucketing() {
+    let conn = &mut get_db_connection(SETUP);
+
+    // Reference values for integer keys
+    let res: (i64, i64) = conn
+        .query_first("select jump_hash(256, 1024), jump_hash(42, 57)")
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(res, (520, 43));
+
+    // String keys are hashed with xxhash64 first
+    let res: i64 = conn
+        .exec_first(
+            "select jump_hash(?, 100) = jump_hash(xxhash64(?), 100)",
+            (TEST, TEST),
+        )
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(res, 1);
+
+    let res: (i64, i64) = conn
+        .exec_first(
+            "select hash_bucket('xxhash64', ?, 10), hash_bucket('sha256', ?, 1000)",
+            (TEST, TEST),
+        )
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(res, (8, 516));
+
+    let res: f64 = conn
+        .exec_first("select hash_to_unit(?)", (TEST,))
+        .unwrap()
+        .unwrap();
+
+    assert!((res - 0.959033409027539).abs() < 1e-12, "{res}");
+
+    let res = conn.exec_drop("select jump_hash('key', 0)", ());
+    assert!(res.is_err());
+}
+
+#[test]
+fn test_rendezvous_hash() {
+    let conn = &mut get_db_connection(SETUP);
+
+    let winner: String = conn
+        .exec_first("select rendezvous_hash(?, 'a', 'b', 'c', 'd')", (TEST,))
+        .unwrap()
+        .unwrap();
+
+    // Removing any other node does not change the winner
+    for removed in ["a", "b", "c", "d"] {
+        if removed == winner {
+            continue;
+        }
+        let nodes: Vec<_> = ["a", "b", "c", "d"]
+            .into_iter()
+            .filter(|n| *n != removed)
+            .map(|n| format!("'{n}'"))
+            .collect();
+
+        let res: String = conn
+            .exec_first(
+                format!("select rendezvous_hash(?, {})", nodes.join(", ")),
+                (TEST,),
+            )
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(res, winner);
+    }
+
+    let res: Option<String> = conn
+        .query_first("select rendezvous_hash(NULL, 'a', 'b')")
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(res, None);
+}
+
+/// 1000 rows numbered from 1, for testing sketches
+const SEQ_ROWS: &str = "(with recursive s (n) as \
+    (select 1 union all select n + 1 from s where n < 1000) select n from s) as t";
+
+#[test]
+fn test_hll() {
+    let conn = &mut get_db_connection(SETUP);
+
+    // Small counts are close to exact
+    let res: i64 = conn
+        .query_first(format!(
+            "select hll_count(hll_agg(n % 500)) from {SEQ_ROWS}"
+        ))
+        .unwrap()
+        .unwrap();
+
+    assert!((490..=510).contains(&res), "{res}");
+
+    // Merging the sketches of two halves estimates the whole
+    let (merged, whole): (i64, i64) = conn
+        .query_first(format!(
+            "select hll_count(hll_merge_agg(sketch)), \
+            (select hll_count(hll_agg(n, 12)) from {SEQ_ROWS}) \
+            from (select hll_agg(n, 12) as sketch from {SEQ_ROWS} group by n % 2) as halves"
+        ))
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(merged, whole);
+    assert!((950..=1050).contains(&whole), "{whole}");
+
+    let res: Option<i64> = conn
+        .query_first("select hll_count('not a sketch')")
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(res, None);
+
+    let res = conn.exec_drop("select hll_agg(1, 30)", ());
+    assert!(res.is_err());
+}
+
+#[test]
+fn test_bloom() {
+    let conn = &mut get_db_connection(SETUP);
+
+    // Every inserted value is found, and few others are
+    let (found, false_positives): (i64, i64) = conn
+        .query_first(format!(
+            "select sum(bloom_contains(f.filter, n)), sum(bloom_contains(f.filter, n + 1000)) \
+            from {SEQ_ROWS}, (select bloom_agg(n, 1000, 0.01) as filter from {SEQ_ROWS}) as f"
+        ))
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(found, 1000);
+    assert!(false_positives < 50, "{false_positives}");
+
+    // Merging filters of each half gives the filter of the whole
+    let res: i64 = conn
+        .query_first(format!(
+            "select bloom_merge_agg(filter) = (select bloom_agg(n, 1000, 0.01) from {SEQ_ROWS}) \
+            from (select bloom_agg(n, 1000, 0.01) as filter from {SEQ_ROWS} group by n % 2) as halves"
+        ))
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(res, 1);
+
+    let res: Option<i64> = conn
+        .query_first("select bloom_contains('not a filter', 1)")
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(res, None);
+
+    let res = conn.exec_drop("select bloom_agg(1, 100000000, 0.01)", ());
+    assert!(res.is_err());
+
+    let res = conn.exec_drop("select bloom_agg(1, 1000, 1.5)", ());
+    assert!(res.is_err());
+}
+
+#[test]
+fn test_similarity() {
+    let conn = &mut get_db_connection(SETUP);
+
+    let (same, similar): (f64, f64) = conn
+        .query_first(
+            "select \
+            minhash_similarity(minhash('the quick brown fox', 128, 3), \
+                minhash('the quick brown fox', 128, 3)), \
+            minhash_similarity(minhash('the quick brown fox', 256, 3), \
+                minhash('the quick brown cat', 256, 3))",
+        )
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(same, 1.0);
+    // The shingle sets have a Jaccard similarity of 0.7
+    assert!((similar - 0.7).abs() < 0.1, "{similar}");
+
+    let res: i64 = conn
+        .query_first("select length(minhash('the quick brown fox', 128, 3))")
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(res, 128 * 8);
+
+    let (near, far): (i64, i64) = conn
+        .query_first(
+            "select \
+            hamming_distance(simhash64('the quick brown fox jumps over the lazy dog'), \
+                simhash64('the quick brown fox jumps over the lazy cat')), \
+            hamming_distance(simhash64('the quick brown fox jumps over the lazy dog'), \
+                simhash64('lorem ipsum dolor sit amet, consectetur'))",
+        )
+        .unwrap()
+        .unwrap();
+
+    assert!(near < far, "{near} {far}");
+
+    let res: (i64, i64, Option<i64>) = conn
+        .query_first("select hamming_distance(5, 3), hamming_distance('abc', 'abd'), hamming_distance('a', 'ab')")
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(res, (2, 3, None));
+
+    let res = conn.exec_drop("select minhash('x', 0, 3)", ());
+    assert!(res.is_err());
+}
+
+#[test]
+fn test_merkle() {
+    let conn = &mut get_db_connection(SETUP);
+
+    let res: String = conn
+        .exec_first(
+            &format!("select merkle_root_agg(v) from {PARTS_ROWS}"),
+            PARTS,
+        )
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(res, RESULT_MERKLE_ROOT);
+
+    let res: String = conn
+        .exec_first(
+            &format!("select hex(merkle_root_agg_bin(v)) from {PARTS_ROWS}"),
+            PARTS,
+        )
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(res, RESULT_MERKLE_ROOT);
+
+    // Order matters
+    let res: String = conn
+        .exec_first(
+            &format!("select merkle_root_agg(v) from {PARTS_ROWS}"),
+            PARTS_REV,
+        )
+        .unwrap()
+        .unwrap();
+
+    assert_ne!(res, RESULT_MERKLE_ROOT);
+
+    // Arguments are concatenated into a single leaf
+    let res: (String, String) = conn
+        .exec_first(
+            "select merkle_leaf_hash(?), hex(merkle_leaf_hash_bin(?, ?, ?))",
+            (TEST, PARTS.0, PARTS.1, PARTS.2),
+        )
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(
+        res,
+        (RESULT_MERKLE_LEAF.to_owned(), RESULT_MERKLE_LEAF.to_owned())
+    );
+
+    // The root of a single leaf is the leaf hash
+    for (root, leaf) in [
+        ("merkle_root_agg", "merkle_leaf_hash"),
+        ("merkle_root_blake3_agg", "merkle_leaf_blake3"),
+    ] {
+        let res: i64 = conn
+            .exec_first(
+                &format!("select {root}(v) = {leaf}(?) from (select ? as v) as t"),
+                (TEST, TEST),
+            )
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(res, 1, "{root}");
+    }
+}
+
+#[test]
+fn test_shake() {
+    let conn = &mut get_db_connection(SETUP);
+
+    let res: (String, String) = conn
+        .exec_first(
+            "select shake128(20, ?), hex(shake128_bin(20, ?, ?, ?))",
+            (TEST, PARTS.0, PARTS.1, PARTS.2),
+        )
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(res.0, RESULT_SHAKE128_20);
+    assert_eq!(res.1, RESULT_SHAKE128_20);
+
+    // Longer output extends shorter output
+    let res: (String, String) = conn
+        .exec_first(
+            "select shake256(100, ?), hex(shake256_bin(32, ?))",
+            (TEST, TEST),
+        )
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(res.0.len(), 200);
+    assert!(res.0.starts_with(&res.1));
+
+    let res = conn.exec_drop("select shake128(-1, 'x')", ());
+    assert!(res.is_err());
+
+    let res: String = conn
+        .exec_first("select ripemd160(sha256_bin(?))", (TEST,))
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(res, RESULT_HASH160);
+}
+
+#[test]
+fn test_sp800_185() {
+    let conn = &mut get_db_connection(SETUP);
+
+    let res: (String, String) = conn
+        .exec_first(
+            "select kmac256('key', 'app', 32, ?), hex(kmac256_bin('key', 'app', 32, ?))",
+            (TEST, TEST),
+        )
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(res.0, RESULT_KMAC256_32);
+    assert_eq!(res.1, RESULT_KMAC256_32);
+
+    // Unlike plain hashes, tuple boundaries change the result
+    let res: (String, String) = conn
+        .exec_first(
+            "select tuplehash128(32, ?, ?, ?), tuplehash128(32, ?)",
+            (PARTS.0, PARTS.1, PARTS.2, TEST),
+        )
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(res.0, RESULT_TUPLEHASH128_PARTS);
+    assert_ne!(res.0, res.1);
+
+    let res: (String, String, String) = conn
+        .exec_first(
+            "select k12(32, ?), k12(32, ?, NULL), k12(32, ?, 'app')",
+            (TEST, TEST, TEST),
+        )
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(res.0, RESULT_K12_32);
+    assert_eq!(res.1, RESULT_K12_32);
+    assert_ne!(res.2, RESULT_K12_32);
+
+    // Customization separates domains, and a `NULL` key gives `NULL`
+    let res: (String, String, Option<String>) = conn
+        .exec_first(
+            "select cshake256('a', 32, ?), cshake256('b', 32, ?), kmac128(NULL, '', 32, ?)",
+            (TEST, TEST, TEST),
+        )
+        .unwrap()
+        .unwrap();
+
+    assert_ne!(res.0, res.1);
+    assert_eq!(res.2, None);
+}
+
+#[test]
+fn test_hash_canonical() {
+    let conn = &mut get_db_connection(SETUP);
+
+    // Plain hashing depends on the type and scale
+    let res: (i64, i64) = conn
+        .exec_first(
+            "select hash('sha256', 1.50) = hash('sha256', 1.5),
+                hash('sha256', 1.5) = hash('sha256', 1.5e0)",
+            (),
+        )
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(res, (0, 0));
+
+    let res: (String, String, String) = conn
+        .exec_first(
+            "select hash_canonical('sha256', cast(1.5 as decimal(10, 4))),
+                hash_canonical('sha256', 1.5e0),
+                hash_canonical('sha256', '1.5')",
+            (),
+        )
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(res.0, res.1);
+    assert_eq!(res.0, res.2);
+
+    let res: (i64, i64, i64) = conn
+        .exec_first(
+            "select hash_canonical('md5', 2) = hash_canonical('md5', 2.000),
+                hash_canonical('md5', -0.0) = hash_canonical('md5', 0),
+                hash_canonical_bin('xxhash64', -0e0) = hash_canonical_bin('xxhash64', 0e0)",
+            (),
+        )
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(res, (1, 1, 1));
+}
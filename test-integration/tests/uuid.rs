@@ -13,6 +13,18 @@ const SETUP: &[&str] = &[
     "create or replace function uuid_generate_v1mc
         returns string
         soname 'libudf_uuid.so'",
+    "create or replace function uuid_generate_v3
+        returns string
+        soname 'libudf_uuid.so'",
+    "create or replace function uuid_generate_v3_bin
+        returns string
+        soname 'libudf_uuid.so'",
+    "create or replace function uuid_generate_v5
+        returns string
+        soname 'libudf_uuid.so'",
+    "create or replace function uuid_generate_v5_bin
+        returns string
+        soname 'libudf_uuid.so'",
     "create or replace function uuid_generate_v4
         returns string
         soname 'libudf_uuid.so'",
@@ -22,6 +34,24 @@ const SETUP: &[&str] = &[
     "create or replace function uuid_generate_v7
         returns string
         soname 'libudf_uuid.so'",
+    "create or replace function uuid_generate_v7_monotonic
+        returns string
+        soname 'libudf_uuid.so'",
+    "create or replace function uuid_v7_lower_bound
+        returns string
+        soname 'libudf_uuid.so'",
+    "create or replace function uuid_v7_upper_bound
+        returns string
+        soname 'libudf_uuid.so'",
+    "create or replace function uuid_generate_v8
+        returns string
+        soname 'libudf_uuid.so'",
+    "create or replace function uuid_v8_pack
+        returns string
+        soname 'libudf_uuid.so'",
+    "create or replace function uuid_v8_unpack
+        returns integer
+        soname 'libudf_uuid.so'",
     "create or replace function uuid_to_bin
         returns string
         soname 'libudf_uuid.so'",
@@ -52,6 +82,21 @@ const SETUP: &[&str] = &[
     "create or replace function uuid_is_valid
         returns integer
         soname 'libudf_uuid.so'",
+    "create or replace function uuid_version
+        returns integer
+        soname 'libudf_uuid.so'",
+    "create or replace function uuid_variant
+        returns string
+        soname 'libudf_uuid.so'",
+    "create or replace function uuid_timestamp
+        returns string
+        soname 'libudf_uuid.so'",
+    "create or replace function uuid_node
+        returns string
+        soname 'libudf_uuid.so'",
+    "create or replace function uuid_clock_seq
+        returns integer
+        soname 'libudf_uuid.so'",
 ];
 
 #[test]
@@ -142,6 +187,44 @@ fn test_generate_v1mc() {
     assert_eq!(uuid.get_version_num(), 1);
 }
 
+#[test]
+fn test_generate_v3_v5() {
+    let conn = &mut get_db_connection(SETUP);
+
+    let res: (String, String) = conn
+        .query_first(
+            "select uuid_generate_v3(uuid_ns_dns(), 'www.example.com'),
+                uuid_generate_v5(uuid_ns_dns(), 'www.example.com')",
+        )
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(res.0, "5df41881-3aed-3515-88a7-2f4a814cf09e");
+    assert_eq!(res.1, "2ed6657d-e927-568b-95e1-2665a8aea6a2");
+
+    // Binary namespaces and output
+    let res: (Vec<u8>, Vec<u8>) = conn
+        .query_first(
+            "select uuid_generate_v3_bin(uuid_to_bin(uuid_ns_url()), 'x'),
+                uuid_generate_v5_bin(uuid_to_bin(uuid_ns_url()), 'x')",
+        )
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(res.0, Uuid::new_v3(&Uuid::NAMESPACE_URL, b"x").as_bytes());
+    assert_eq!(res.1, Uuid::new_v5(&Uuid::NAMESPACE_URL, b"x").as_bytes());
+
+    let res: Option<String> = conn
+        .query_first("select uuid_generate_v5(uuid_ns_dns(), NULL)")
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(res, None);
+
+    let res = conn.query_drop("select uuid_generate_v5('not a uuid', 'x')");
+    assert!(res.is_err());
+}
+
 #[test]
 fn test_generate_v4() {
     let conn = &mut get_db_connection(SETUP);
@@ -179,6 +262,18 @@ fn test_generate_v6() {
 
     assert_eq!(uuid.get_version_num(), 6);
     assert!(uuid.as_bytes().ends_with(node_id.as_bytes()));
+
+    // Any single argument other than a 6-byte node ID is a time
+    let res: (String, String) = conn
+        .query_first("select uuid_generate_v6('2022-02-22 19:22:22'), uuid_generate_v6(1645557742)")
+        .unwrap()
+        .unwrap();
+
+    assert!(res.0.starts_with("1ec9414c-232a-6b00-"), "{}", res.0);
+    assert!(res.1.starts_with("1ec9414c-232a-6b00-"), "{}", res.1);
+
+    let res = conn.query_drop("select uuid_generate_v6('abc')");
+    assert!(res.is_err());
 }
 
 #[test]
@@ -195,6 +290,124 @@ fn test_generate_v7() {
     assert_eq!(uuid.get_version_num(), 7);
 }
 
+#[test]
+fn test_generate_with_timestamp() {
+    let conn = &mut get_db_connection(SETUP);
+
+    let res: (String, String, String) = conn
+        .query_first(
+            "select uuid_generate_v1('2022-02-22 19:22:22'),
+                uuid_generate_v6(1645557742, 'abcdef'),
+                uuid_generate_v7(cast('2022-02-22 19:22:22.000' as datetime(3)))",
+        )
+        .unwrap()
+        .unwrap();
+
+    assert!(res.0.starts_with("c232ab00-9414-11ec-"), "{}", res.0);
+    assert!(res.1.starts_with("1ec9414c-232a-6b00-"), "{}", res.1);
+    assert!(Uuid::try_parse(&res.1)
+        .unwrap()
+        .as_bytes()
+        .ends_with(b"abcdef"));
+    assert!(res.2.starts_with("017f22e2-79b0-7"), "{}", res.2);
+
+    let res: (String, String, String) = conn
+        .query_first(
+            "select uuid_v7_lower_bound(1645557742), uuid_v7_upper_bound(1645557742),
+                uuid_generate_v7(1645557742)",
+        )
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(res.0, "017f22e2-79b0-7000-8000-000000000000");
+    assert_eq!(res.1, "017f22e2-79b0-7fff-bfff-ffffffffffff");
+    assert!(res.0 <= res.2 && res.2 <= res.1);
+
+    let res: (Option<String>, Option<String>) = conn
+        .query_first("select uuid_generate_v7(NULL), uuid_generate_v7(-1)")
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(res, (None, None));
+
+    let res = conn.query_drop("select uuid_generate_v7('not a time')");
+    assert!(res.is_err());
+
+    // Days past the end of the month are not valid dates
+    for date in ["2022-02-31 00:00:00", "2023-04-31", "2023-02-29"] {
+        let res = conn.query_drop(format!("select uuid_v7_lower_bound('{date}')"));
+        assert!(res.is_err(), "{date}");
+    }
+
+    let res: String = conn
+        .query_first("select uuid_v7_lower_bound('2024-02-29')")
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(res, "018df229-2800-7000-8000-000000000000");
+}
+
+#[test]
+fn test_generate_v7_monotonic() {
+    let conn = &mut get_db_connection(SETUP);
+
+    let res: Vec<String> = conn
+        .query(
+            "with recursive seq (n) as (
+                select 1 union all select n + 1 from seq where n < 1000
+            )
+            select uuid_generate_v7_monotonic() from seq",
+        )
+        .unwrap();
+
+    assert_eq!(res.len(), 1000);
+    assert!(res.windows(2).all(|w| w[0] < w[1]));
+
+    let uuid = Uuid::try_parse(&res[0]).unwrap();
+    assert_eq!(uuid.get_version_num(), 7);
+}
+
+#[test]
+fn test_generate_v8() {
+    let conn = &mut get_db_connection(SETUP);
+
+    let res: String = conn
+        .query_first("select uuid_generate_v8(unhex('00112233445566778899aabbccddeeff'))")
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(res, "00112233-4455-8677-8899-aabbccddeeff");
+
+    let res: String = conn
+        .query_first("select uuid_v8_pack(1234, 56)")
+        .unwrap()
+        .unwrap();
+
+    let uuid = Uuid::try_parse(&res).unwrap();
+    assert_eq!(uuid.get_version_num(), 8);
+
+    let res: (i64, i64, i64) = conn
+        .exec_first(
+            "select uuid_v8_unpack(?, 'high'), uuid_v8_unpack(?, 'low'),
+                uuid_v8_unpack(uuid_to_bin(?), 'LOW')",
+            (&res, &res, &res),
+        )
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(res, (1234, 56, 56));
+
+    let res: Option<i64> = conn
+        .query_first("select uuid_v8_unpack(uuid_generate_v4(), 'high')")
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(res, None);
+
+    let res = conn.query_drop("select uuid_v8_pack(-1, 0)");
+    assert!(res.is_err());
+}
+
 #[test]
 fn test_valid() {
     let conn = &mut get_db_connection(SETUP);
@@ -207,6 +420,56 @@ fn test_valid() {
     assert_eq!(res, 1);
 }
 
+#[test]
+fn test_inspect() {
+    let conn = &mut get_db_connection(SETUP);
+
+    // Example v1 UUID from RFC 9562
+    let v1 = "c232ab00-9414-11ec-b3c8-9f6bdeced846";
+    let res: (i64, String, String, String, i64) = conn
+        .exec_first(
+            "select uuid_version(?), uuid_variant(?), uuid_timestamp(?), uuid_node(?),
+                uuid_clock_seq(?)",
+            (v1, v1, v1, v1, v1),
+        )
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(
+        res,
+        (
+            1,
+            "RFC4122".to_owned(),
+            "2022-02-22 19:22:22.000000".to_owned(),
+            "9f:6b:de:ce:d8:46".to_owned(),
+            0x33c8
+        )
+    );
+
+    // Binary input, and the result works as a `DATETIME(6)`
+    let res: (i64, String) = conn
+        .query_first(
+            "select uuid_version(uuid_to_bin(uuid_generate_v7())),
+                cast(uuid_timestamp(uuid_to_bin('017f22e2-79b0-7cc3-98c4-dc0c0c07398f'))
+                    as datetime(6))",
+        )
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(res.0, 7);
+    assert_eq!(res.1, "2022-02-22 19:22:22.000000");
+
+    let res: (Option<String>, Option<String>, Option<i64>, Option<i64>) = conn
+        .query_first(
+            "select uuid_timestamp(uuid_generate_v4()), uuid_node(uuid_generate_v7()),
+                uuid_clock_seq(uuid_generate_v4()), uuid_version('not a uuid')",
+        )
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(res, (None, None, None, None));
+}
+
 const INPUT: &str = "6ccd780c-baba-1026-9564-5b8c656024db";
 const NORMAL: UuidBytes = hex_literal::hex!("6CCD780CBABA102695645B8C656024DB");
 const SWAPPED: UuidBytes = hex_literal::hex!("1026BABA6CCD780C95645B8C656024DB");
@@ -0,0 +1,271 @@
+//! Blake3 keyed hashing, key derivation, and extendable output
+//!
+//! These all take a leading argument (key, context, or output length) followed by
+//! any number of data arguments. Each is available as hex or binary, and with a
+//! threaded (`_thd`) version that hashes on the pool in `blake3_pool`.
+
+use udf::prelude::*;
+
+use crate::{blake3_pool, hash_arg};
+
+/// Largest output that `blake3_xof` will produce (1 MiB)
+const MAX_XOF_LEN: i64 = 1 << 20;
+/// Required key length for keyed hashing
+const KEY_LEN: usize = blake3::KEY_LEN;
+/// Output length of keyed and derived hashes
+const OUT_LEN: usize = blake3::OUT_LEN;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Blake3Mode {
+    /// First argument is a 32-byte key
+    Keyed,
+    /// First argument is a context string
+    DeriveKey,
+    /// First argument is the output length
+    Xof,
+}
+
+/// Shared implementation for all functions in this module
+#[derive(Debug)]
+struct Blake3Ext {
+    mode: Blake3Mode,
+    threaded: bool,
+    hex: bool,
+    /// Binary result
+    ret: Vec<u8>,
+    /// Hex result, if `hex` is set
+    hex_ret: Vec<u8>,
+}
+
+impl Blake3Ext {
+    fn init(
+        cfg: &UdfCfg<Init>,
+        args: &ArgList<Init>,
+        fn_name: &str,
+        mode: Blake3Mode,
+        threaded: bool,
+        hex: bool,
+    ) -> Result<Self, String> {
+        let usage = match mode {
+            Blake3Mode::Keyed => "key, data...",
+            Blake3Mode::DeriveKey => "context, key_material...",
+            Blake3Mode::Xof => "length, data...",
+        };
+        let Some(mut first) = args.get(0) else {
+            return Err(format!(
+                "{fn_name} takes at least 1 argument but got 0 (usage: `{fn_name}({usage})`)"
+            ));
+        };
+
+        let mut max_len = OUT_LEN as i64;
+        match mode {
+            Blake3Mode::Keyed => {
+                // Values must be checked before setting coercion
+                if let Some(key) = first.value().as_bytes() {
+                    if key.len() != KEY_LEN {
+                        return Err(format!(
+                            "{fn_name} requires a key of {KEY_LEN} bytes but got {}",
+                            key.len()
+                        ));
+                    }
+                }
+                first.set_type_coercion(SqlType::String);
+            }
+            Blake3Mode::DeriveKey => {
+                if let Some(context) = first.value().as_bytes() {
+                    if std::str::from_utf8(context).is_err() {
+                        return Err(format!("{fn_name} context must be valid UTF-8"));
+                    }
+                }
+                first.set_type_coercion(SqlType::String);
+            }
+            Blake3Mode::Xof => {
+                max_len = MAX_XOF_LEN;
+                if let Some(len) = first.value().as_int() {
+                    if !(0..=MAX_XOF_LEN).contains(&len) {
+                        return Err(format!(
+                            "{fn_name} output length must be between 0 and {MAX_XOF_LEN}, got {len}"
+                        ));
+                    }
+                    max_len = len;
+                }
+                first.set_type_coercion(SqlType::Int);
+            }
+        }
+
+        let max_len = if hex { max_len * 2 } else { max_len };
+        cfg.set_max_len(max_len.unsigned_abs());
+
+        Ok(Self {
+            mode,
+            threaded,
+            hex,
+            ret: Vec::new(),
+            hex_ret: Vec::new(),
+        })
+    }
+
+    fn process(&mut self, args: &ArgList<Process>) -> Option<&[u8]> {
+        let first = args.get(0).unwrap().value();
+        let mut out_len = OUT_LEN;
+        let mut hasher = match self.mode {
+            Blake3Mode::Keyed => blake3::Hasher::new_keyed(first.as_bytes()?.try_into().ok()?),
+            Blake3Mode::DeriveKey => blake3::Hasher::new_derive_key(first.as_string()?),
+            Blake3Mode::Xof => {
+                let len = first.as_int()?;
+                if !(0..=MAX_XOF_LEN).contains(&len) {
+                    return None;
+                }
+                out_len = len.unsigned_abs() as usize;
+                blake3::Hasher::new()
+            }
+        };
+
+        args.iter().skip(1).for_each(|arg| {
+            hash_arg(arg, |buf| {
+                if self.threaded {
+                    blake3_pool::update_threaded(&mut hasher, buf);
+                } else {
+                    hasher.update(buf);
+                }
+            })
+        });
+
+        self.ret.resize(out_len, 0);
+        hasher.finalize_xof().fill(&mut self.ret);
+
+        if !self.hex {
+            return Some(&self.ret);
+        }
+
+        self.hex_ret.resize(out_len * 2, 0);
+        data_encoding::HEXUPPER.encode_mut(&self.ret, &mut self.hex_ret);
+        Some(&self.hex_ret)
+    }
+}
+
+// Create a UDF named `$fn_name` with the given mode, threading, and output
+macro_rules! blake3_ext_udf {
+    ($fn_name:ident, $mode:expr, threaded: $threaded:expr, hex: $hex:expr) => {
+        #[allow(non_camel_case_types)]
+        #[derive(Debug)]
+        struct $fn_name {
+            ext: Blake3Ext,
+        }
+
+        #[register]
+        impl BasicUdf for $fn_name {
+            type Returns<'a> = Option<&'a [u8]>;
+
+            fn init(cfg: &UdfCfg<Init>, args: &ArgList<Init>) -> Result<Self, String> {
+                Blake3Ext::init(cfg, args, stringify!($fn_name), $mode, $threaded, $hex)
+                    .map(|ext| Self { ext })
+            }
+
+            fn process<'a>(
+                &'a mut self,
+                _cfg: &UdfCfg<Process>,
+                args: &ArgList<Process>,
+                _error: Option<NonZeroU8>,
+            ) -> Result<Self::Returns<'a>, ProcessError> {
+                Ok(self.ext.process(args))
+            }
+        }
+    };
+}
+
+blake3_ext_udf!(blake3_keyed, Blake3Mode::Keyed, threaded: false, hex: true);
+blake3_ext_udf!(blake3_keyed_bin, Blake3Mode::Keyed, threaded: false, hex: false);
+blake3_ext_udf!(blake3_keyed_thd, Blake3Mode::Keyed, threaded: true, hex: true);
+blake3_ext_udf!(blake3_keyed_thd_bin, Blake3Mode::Keyed, threaded: true, hex: false);
+blake3_ext_udf!(blake3_derive_key, Blake3Mode::DeriveKey, threaded: false, hex: true);
+blake3_ext_udf!(blake3_derive_key_bin, Blake3Mode::DeriveKey, threaded: false, hex: false);
+blake3_ext_udf!(blake3_derive_key_thd, Blake3Mode::DeriveKey, threaded: true, hex: true);
+blake3_ext_udf!(blake3_derive_key_thd_bin, Blake3Mode::DeriveKey, threaded: true, hex: false);
+blake3_ext_udf!(blake3_xof, Blake3Mode::Xof, threaded: false, hex: true);
+blake3_ext_udf!(blake3_xof_bin, Blake3Mode::Xof, threaded: false, hex: false);
+blake3_ext_udf!(blake3_xof_thd, Blake3Mode::Xof, threaded: true, hex: true);
+blake3_ext_udf!(blake3_xof_thd_bin, Blake3Mode::Xof, threaded: true, hex: false);
+
+#[cfg(test)]
+mod tests {
+    use udf::mock::*;
+
+    use super::*;
+
+    const KEY: &str = "whats the Elvish word for friend";
+    const CONTEXT: &str = "BLAKE3 2019-12-27 16:29:52 test vectors context";
+
+    #[test]
+    fn test_keyed() {
+        let mut cfg = MockUdfCfg::new();
+        let mut args = mock_args![
+            (KEY, "", false),
+            ("Hello, ", "", false),
+            ("world!", "", false)
+        ];
+        let expected = blake3::keyed_hash(KEY.as_bytes().try_into().unwrap(), b"Hello, world!");
+
+        let mut udf = blake3_keyed_bin::init(cfg.as_init(), args.as_init()).unwrap();
+        let res = udf
+            .process(cfg.as_process(), args.as_process(), None)
+            .unwrap();
+        assert_eq!(res.unwrap(), expected.as_bytes());
+
+        let mut udf = blake3_keyed_thd::init(cfg.as_init(), args.as_init()).unwrap();
+        let res = udf
+            .process(cfg.as_process(), args.as_process(), None)
+            .unwrap();
+        assert_eq!(res.unwrap(), expected.to_hex().to_uppercase().as_bytes());
+    }
+
+    #[test]
+    fn test_keyed_wrong_len() {
+        let mut cfg = MockUdfCfg::new();
+        let mut args = mock_args![("short key", "", false), ("data", "", false)];
+        let res = blake3_keyed::init(cfg.as_init(), args.as_init());
+        assert_eq!(
+            res.unwrap_err(),
+            "blake3_keyed requires a key of 32 bytes but got 9"
+        );
+    }
+
+    #[test]
+    fn test_derive_key() {
+        let mut cfg = MockUdfCfg::new();
+        let mut args = mock_args![(CONTEXT, "", false), ("key material", "", false)];
+        let expected = blake3::derive_key(CONTEXT, b"key material");
+
+        let mut udf = blake3_derive_key_bin::init(cfg.as_init(), args.as_init()).unwrap();
+        let res = udf
+            .process(cfg.as_process(), args.as_process(), None)
+            .unwrap();
+        assert_eq!(res.unwrap(), expected);
+    }
+
+    #[test]
+    fn test_xof() {
+        let mut cfg = MockUdfCfg::new();
+        let mut args = mock_args![(Int 100, "", false), ("Hello, world!", "", false)];
+        let mut expected = [0u8; 100];
+        blake3::Hasher::new()
+            .update(b"Hello, world!")
+            .finalize_xof()
+            .fill(&mut expected);
+
+        let mut udf = blake3_xof_bin::init(cfg.as_init(), args.as_init()).unwrap();
+        let res = udf
+            .process(cfg.as_process(), args.as_process(), None)
+            .unwrap();
+        assert_eq!(res.unwrap(), expected);
+
+        // The first 32 bytes match the regular hash
+        let mut udf = blake3_xof::init(cfg.as_init(), args.as_init()).unwrap();
+        let res = udf
+            .process(cfg.as_process(), args.as_process(), None)
+            .unwrap();
+        assert!(res
+            .unwrap()
+            .starts_with(b"EDE5C0B10F2EC4979C69B52F61E42FF5B413519CE09BE0F14D098DCFE5F6F98D"));
+    }
+}
@@ -0,0 +1,97 @@
+//! A dedicated thread pool for multithreaded blake3
+//!
+//! `blake3` and `blake3_bin` switch to multithreaded hashing for any argument of at
+//! least `UDF_HASH_BLAKE3_THRESHOLD` bytes (default 128 KiB, about where threading
+//! starts to help). Setting it to 0 disables the switch. The `_thd` functions always
+//! hash with threads.
+//!
+//! Threaded hashing runs on a pool owned by this plugin rather than rayon's global
+//! pool, so that it cannot occupy every core the server has. The pool has
+//! `UDF_HASH_THREADS` threads (default: the number of cores, at most 4). Both
+//! variables are read from the server's environment the first time they are needed.
+
+use std::env;
+use std::thread::available_parallelism;
+
+use once_cell::sync::OnceCell;
+use rayon_core::{ThreadPool, ThreadPoolBuilder};
+
+const THRESHOLD_ENV: &str = "UDF_HASH_BLAKE3_THRESHOLD";
+const THREADS_ENV: &str = "UDF_HASH_THREADS";
+const DEFAULT_THRESHOLD: usize = 128 * 1024;
+/// Upper bound for the default thread count, not for a configured one
+const DEFAULT_MAX_THREADS: usize = 4;
+
+/// Parse a size from the environment, `None` if unset or invalid
+fn env_usize(name: &str) -> Option<usize> {
+    env::var(name).ok()?.trim().parse().ok()
+}
+
+/// Minimum argument size for `blake3` to use the pool, `None` if disabled
+fn threshold() -> Option<usize> {
+    static THRESHOLD: OnceCell<Option<usize>> = OnceCell::new();
+    *THRESHOLD.get_or_init(|| {
+        let n = env_usize(THRESHOLD_ENV).unwrap_or(DEFAULT_THRESHOLD);
+        (n > 0).then_some(n)
+    })
+}
+
+/// The shared pool, `None` if it could not be created
+fn pool() -> Option<&'static ThreadPool> {
+    static POOL: OnceCell<Option<ThreadPool>> = OnceCell::new();
+    POOL.get_or_init(|| {
+        let threads = env_usize(THREADS_ENV)
+            .filter(|n| *n > 0)
+            .unwrap_or_else(|| {
+                available_parallelism().map_or(1, |n| n.get().min(DEFAULT_MAX_THREADS))
+            });
+        ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .thread_name(|i| format!("udf-hash-{i}"))
+            .build()
+            .ok()
+    })
+    .as_ref()
+}
+
+/// Hash with threads on the plugin's pool, or on this thread if there is no pool
+pub(crate) fn update_threaded(hasher: &mut blake3::Hasher, buf: &[u8]) {
+    match pool() {
+        Some(pool) => pool.install(|| {
+            hasher.update_rayon(buf);
+        }),
+        None => {
+            hasher.update(buf);
+        }
+    }
+}
+
+/// Hash with threads if `buf` is at least the threshold size
+pub(crate) fn update_auto(hasher: &mut blake3::Hasher, buf: &[u8]) {
+    match threshold() {
+        Some(min) if buf.len() >= min => update_threaded(hasher, buf),
+        _ => {
+            hasher.update(buf);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_update_matches() {
+        let buf: Vec<u8> = (0..1_000_000u32).map(|i| i as u8).collect();
+        let expected = blake3::hash(&buf);
+
+        let mut hasher = blake3::Hasher::new();
+        update_threaded(&mut hasher, &buf);
+        assert_eq!(hasher.finalize(), expected);
+
+        let mut hasher = blake3::Hasher::new();
+        update_auto(&mut hasher, &buf[..10]);
+        update_auto(&mut hasher, &buf[10..]);
+        assert_eq!(hasher.finalize(), expected);
+    }
+}
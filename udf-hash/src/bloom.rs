@@ -0,0 +1,442 @@
+//! Bloom filters for set membership
+//!
+//! `bloom_agg(col, expected_items, fp_rate)` builds a filter sized for the expected
+//! number of items and false positive rate, `bloom_merge_agg(filter)` unions filters
+//! of the same size, and `bloom_contains(filter, value)` tests membership. Filters
+//! are limited to 2^27 bits (16 MiB).
+//!
+//! # Format
+//!
+//! A serialized filter is:
+//!
+//! - 1 byte: format version, currently `1`
+//! - 1 byte: `k`, the number of hash functions
+//! - 4 bytes: `m`, the number of bits, as a little endian `u32`
+//! - `ceil(m / 8)` bytes: the bits, where bit `i` is `byte[i / 8] & (1 << (i % 8))`
+//!
+//! A value sets bits `xxhash64_seed(i, value) mod m` for each `i` in `0..k`, so a
+//! filter can be checked outside of SQL with any xxhash64 implementation.
+
+use udf::prelude::*;
+use xxhash_rust::xxh64::xxh64;
+
+use crate::{hash_arg, is_null};
+
+const FILTER_VERSION: u8 = 1;
+/// Length of the header before the bits
+const HEADER_LEN: usize = 6;
+/// Largest filter we create or merge, 16 MiB. Each group of an aggregate has its
+/// own filter, so this bounds the memory per group.
+const MAX_BITS: u64 = 1 << 27;
+const MAX_HASHES: u64 = 32;
+
+/// An owned Bloom filter
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Filter {
+    hashes: u8,
+    bit_count: u32,
+    bits: Vec<u8>,
+}
+
+impl Filter {
+    /// Number of bits needed for `items` entries with the given false positive rate
+    fn bits_for(items: u64, fp_rate: f64) -> u64 {
+        let ln2 = std::f64::consts::LN_2;
+        (-(items as f64) * fp_rate.ln() / (ln2 * ln2)).ceil() as u64
+    }
+
+    /// Size a filter for `items` entries with the given false positive rate
+    fn with_rate(items: u64, fp_rate: f64) -> Self {
+        let ln2 = std::f64::consts::LN_2;
+        let bit_count = Self::bits_for(items, fp_rate).clamp(8, MAX_BITS);
+        let hashes = (bit_count as f64 / items as f64 * ln2).round() as u64;
+        Self::new(hashes.clamp(1, MAX_HASHES) as u8, bit_count as u32)
+    }
+
+    fn new(hashes: u8, bit_count: u32) -> Self {
+        Self {
+            hashes,
+            bit_count,
+            bits: vec![0; byte_len(bit_count)],
+        }
+    }
+
+    fn insert(&mut self, value: &[u8]) {
+        for idx in bit_indices(value, self.hashes, self.bit_count) {
+            self.bits[idx / 8] |= 1 << (idx % 8);
+        }
+    }
+
+    fn merge(&mut self, other: FilterRef) -> bool {
+        if other.hashes != self.hashes || other.bit_count != self.bit_count {
+            return false;
+        }
+        self.bits
+            .iter_mut()
+            .zip(other.bits)
+            .for_each(|(a, b)| *a |= b);
+        true
+    }
+
+    fn serialize_into(&self, out: &mut Vec<u8>) {
+        out.clear();
+        out.reserve(HEADER_LEN + self.bits.len());
+        out.extend_from_slice(&[FILTER_VERSION, self.hashes]);
+        out.extend_from_slice(&self.bit_count.to_le_bytes());
+        out.extend_from_slice(&self.bits);
+    }
+}
+
+/// A serialized filter that has been checked, borrowing the bits
+#[derive(Clone, Copy, Debug)]
+struct FilterRef<'a> {
+    hashes: u8,
+    bit_count: u32,
+    bits: &'a [u8],
+}
+
+impl<'a> FilterRef<'a> {
+    /// Parse a serialized filter, `None` if it is not valid
+    fn parse(buf: &'a [u8]) -> Option<Self> {
+        if buf.len() < HEADER_LEN {
+            return None;
+        }
+        let (header, bits) = buf.split_at(HEADER_LEN);
+        let (version, hashes) = (header[0], header[1]);
+        let bit_count = u32::from_le_bytes([header[2], header[3], header[4], header[5]]);
+        if version != FILTER_VERSION
+            || hashes == 0
+            || bit_count == 0
+            || bits.len() != byte_len(bit_count)
+        {
+            return None;
+        }
+        Some(Self {
+            hashes,
+            bit_count,
+            bits,
+        })
+    }
+
+    fn contains(&self, value: &[u8]) -> bool {
+        bit_indices(value, self.hashes, self.bit_count)
+            .all(|idx| self.bits[idx / 8] & (1 << (idx % 8)) != 0)
+    }
+}
+
+fn byte_len(bit_count: u32) -> usize {
+    (bit_count as usize + 7) / 8
+}
+
+/// The bits set by a value: `xxhash64(value, seed = i) mod m` for `i` in `0..k`
+fn bit_indices(value: &[u8], hashes: u8, bit_count: u32) -> impl Iterator<Item = usize> + '_ {
+    (0..u64::from(hashes)).map(move |seed| (xxh64(value, seed) % u64::from(bit_count)) as usize)
+}
+
+/// Read the constant sizing arguments to `bloom_agg`
+fn sizing_init(args: &ArgList<Init>) -> Result<(u64, f64), String> {
+    let mut items_arg = args.get(1).unwrap();
+    let mut rate_arg = args.get(2).unwrap();
+
+    // Values must be checked before setting coercion
+    let items = match items_arg.value().as_int() {
+        Some(n) if n > 0 => n as u64,
+        Some(n) => {
+            return Err(format!(
+                "bloom_agg: expected_items must be positive, got {n}"
+            ))
+        }
+        None => return Err("bloom_agg: expected_items must be a constant integer".to_owned()),
+    };
+    let fp_rate = match rate_arg.value() {
+        SqlResult::Real(Some(f)) => Some(f),
+        SqlResult::Decimal(Some(d)) => d.parse().ok(),
+        SqlResult::Int(Some(i)) => Some(i as f64),
+        _ => None,
+    };
+    let fp_rate = match fp_rate {
+        Some(f) if f > 0.0 && f < 1.0 => f,
+        Some(f) => {
+            return Err(format!(
+                "bloom_agg: fp_rate must be between 0 and 1 (exclusive), got {f}"
+            ))
+        }
+        None => return Err("bloom_agg: fp_rate must be a constant number".to_owned()),
+    };
+    let bits = Filter::bits_for(items, fp_rate);
+    if bits > MAX_BITS {
+        return Err(format!(
+            "bloom_agg: {items} items with fp_rate {fp_rate} need {bits} bits, but \
+            filters may have at most {MAX_BITS} bits (16 MiB)"
+        ));
+    }
+
+    items_arg.set_type_coercion(SqlType::Int);
+    rate_arg.set_type_coercion(SqlType::Real);
+    Ok((items, fp_rate))
+}
+
+/// `bloom_agg(col, expected_items, fp_rate)`, a filter of every non-null value
+struct BloomAgg {
+    filter: Filter,
+    ret: Vec<u8>,
+}
+
+#[register(name = "bloom_agg")]
+impl BasicUdf for BloomAgg {
+    type Returns<'a> = &'a [u8];
+
+    fn init(cfg: &UdfCfg<Init>, args: &ArgList<Init>) -> Result<Self, String> {
+        if args.len() != 3 {
+            return Err(format!(
+                "bloom_agg takes 3 arguments but got {} \
+                (usage: `bloom_agg(col, expected_items, fp_rate)`)",
+                args.len()
+            ));
+        }
+        let (items, fp_rate) = sizing_init(args)?;
+        let filter = Filter::with_rate(items, fp_rate);
+        cfg.set_max_len((HEADER_LEN + filter.bits.len()) as u64);
+        Ok(Self {
+            filter,
+            ret: Vec::new(),
+        })
+    }
+
+    fn process<'a>(
+        &'a mut self,
+        _cfg: &UdfCfg<Process>,
+        _args: &ArgList<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<Self::Returns<'a>, ProcessError> {
+        self.filter.serialize_into(&mut self.ret);
+        Ok(&self.ret)
+    }
+}
+
+#[register(name = "bloom_agg")]
+impl AggregateUdf for BloomAgg {
+    fn clear(
+        &mut self,
+        _cfg: &UdfCfg<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<(), NonZeroU8> {
+        self.filter.bits.fill(0);
+        Ok(())
+    }
+
+    fn add(
+        &mut self,
+        _cfg: &UdfCfg<Process>,
+        args: &ArgList<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<(), NonZeroU8> {
+        let arg = args.get(0).unwrap();
+        if !is_null(&arg.value()) {
+            hash_arg(arg, |buf| self.filter.insert(buf));
+        }
+        Ok(())
+    }
+}
+
+/// `bloom_merge_agg(filter)`, the union of every non-null filter. The result is
+/// `NULL` if any filter is invalid or the filters have different sizes.
+struct BloomMergeAgg {
+    filter: Option<Filter>,
+    invalid: bool,
+    ret: Vec<u8>,
+}
+
+#[register(name = "bloom_merge_agg")]
+impl BasicUdf for BloomMergeAgg {
+    type Returns<'a> = Option<&'a [u8]>;
+
+    fn init(cfg: &UdfCfg<Init>, args: &ArgList<Init>) -> Result<Self, String> {
+        if args.len() != 1 {
+            return Err(format!(
+                "bloom_merge_agg takes 1 argument but got {} \
+                (usage: `bloom_merge_agg(filter)`)",
+                args.len()
+            ));
+        }
+        args.get(0).unwrap().set_type_coercion(SqlType::String);
+        cfg.set_max_len(HEADER_LEN as u64 + MAX_BITS / 8);
+        Ok(Self {
+            filter: None,
+            invalid: false,
+            ret: Vec::new(),
+        })
+    }
+
+    fn process<'a>(
+        &'a mut self,
+        _cfg: &UdfCfg<Process>,
+        _args: &ArgList<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<Self::Returns<'a>, ProcessError> {
+        if self.invalid {
+            return Ok(None);
+        }
+        let Some(filter) = &self.filter else {
+            return Ok(None);
+        };
+        filter.serialize_into(&mut self.ret);
+        Ok(Some(&self.ret))
+    }
+}
+
+#[register(name = "bloom_merge_agg")]
+impl AggregateUdf for BloomMergeAgg {
+    fn clear(
+        &mut self,
+        _cfg: &UdfCfg<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<(), NonZeroU8> {
+        self.filter = None;
+        self.invalid = false;
+        Ok(())
+    }
+
+    fn add(
+        &mut self,
+        _cfg: &UdfCfg<Process>,
+        args: &ArgList<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<(), NonZeroU8> {
+        let value = args.get(0).unwrap().value();
+        let Some(buf) = value.as_bytes() else {
+            return Ok(());
+        };
+        let Some(other) = FilterRef::parse(buf).filter(|f| u64::from(f.bit_count) <= MAX_BITS)
+        else {
+            self.invalid = true;
+            return Ok(());
+        };
+        match &mut self.filter {
+            Some(filter) => self.invalid |= !filter.merge(other),
+            None => {
+                self.filter = Some(Filter {
+                    hashes: other.hashes,
+                    bit_count: other.bit_count,
+                    bits: other.bits.to_owned(),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// `bloom_contains(filter, value)`, 1 if the value may be in the filter and 0 if it
+/// definitely is not
+struct BloomContains;
+
+#[register(name = "bloom_contains")]
+impl BasicUdf for BloomContains {
+    type Returns<'a> = Option<i64>;
+
+    fn init(_cfg: &UdfCfg<Init>, args: &ArgList<Init>) -> Result<Self, String> {
+        if args.len() != 2 {
+            return Err(format!(
+                "bloom_contains takes 2 arguments but got {} \
+                (usage: `bloom_contains(filter, value)`)",
+                args.len()
+            ));
+        }
+        args.get(0).unwrap().set_type_coercion(SqlType::String);
+        Ok(Self)
+    }
+
+    fn process<'a>(
+        &'a mut self,
+        _cfg: &UdfCfg<Process>,
+        args: &ArgList<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<Self::Returns<'a>, ProcessError> {
+        let filter_arg = args.get(0).unwrap().value();
+        let value = args.get(1).unwrap();
+        let Some(filter) = filter_arg.as_bytes().and_then(FilterRef::parse) else {
+            return Ok(None);
+        };
+        if is_null(&value.value()) {
+            return Ok(None);
+        }
+        Ok(Some(hash_arg(value, |buf| filter.contains(buf)).into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use udf::mock::*;
+
+    use super::*;
+
+    #[test]
+    fn test_sizing() {
+        // 1% false positives needs about 9.6 bits and 7 hashes per item
+        let filter = Filter::with_rate(1000, 0.01);
+        assert_eq!(filter.bit_count, 9586);
+        assert_eq!(filter.hashes, 7);
+        assert_eq!(filter.bits.len(), 1199);
+
+        let filter = Filter::with_rate(1, 0.5);
+        assert_eq!(filter.bit_count, 8);
+        assert_eq!(filter.hashes, 6);
+
+        // Filters larger than `MAX_BITS` are refused up front
+        let mut cfg = MockUdfCfg::new();
+        let mut args =
+            mock_args![(Int 1, "", false), (Int 14_000_000, "", false), (0.01, "", false)];
+        assert!(BloomAgg::init(cfg.as_init(), args.as_init()).is_ok());
+        let mut args =
+            mock_args![(Int 1, "", false), (Int 100_000_000, "", false), (0.01, "", false)];
+        let Err(err) = BloomAgg::init(cfg.as_init(), args.as_init()) else {
+            panic!("expected an error");
+        };
+        assert_eq!(
+            err,
+            "bloom_agg: 100000000 items with fp_rate 0.01 need 958505838 bits, but \
+            filters may have at most 134217728 bits (16 MiB)"
+        );
+    }
+
+    #[test]
+    fn test_contains() {
+        let mut filter = Filter::with_rate(1000, 0.01);
+        (0..1000u64).for_each(|i| filter.insert(&i.to_le_bytes()));
+
+        let mut buf = Vec::new();
+        filter.serialize_into(&mut buf);
+        let parsed = FilterRef::parse(&buf).unwrap();
+
+        assert!((0..1000u64).all(|i| parsed.contains(&i.to_le_bytes())));
+        let false_positives = (1000..11_000u64)
+            .filter(|i| parsed.contains(&i.to_le_bytes()))
+            .count();
+        assert!(false_positives < 150, "{false_positives}");
+    }
+
+    #[test]
+    fn test_format() {
+        let mut filter = Filter::new(2, 16);
+        filter.insert(b"x");
+        let mut buf = Vec::new();
+        filter.serialize_into(&mut buf);
+
+        let mut expected = vec![1, 2, 16, 0, 0, 0, 0, 0];
+        for seed in 0..2 {
+            let idx = (xxh64(b"x", seed) % 16) as usize;
+            expected[HEADER_LEN + idx / 8] |= 1 << (idx % 8);
+        }
+        assert_eq!(buf, expected);
+
+        assert!(FilterRef::parse(&buf[..buf.len() - 1]).is_none());
+        assert!(FilterRef::parse(&[]).is_none());
+
+        // Filters of different sizes cannot be merged
+        let mut other = Filter::new(2, 24);
+        assert!(!other.merge(FilterRef::parse(&buf).unwrap()));
+        let mut other = Filter::new(2, 16);
+        assert!(other.merge(FilterRef::parse(&buf).unwrap()));
+        assert_eq!(other, filter);
+    }
+}
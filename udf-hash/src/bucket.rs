@@ -0,0 +1,300 @@
+//! Consistent hashing, bucketing, and sampling
+//!
+//! These all use xxhash64 with seed 0 unless an algorithm is given, so results are
+//! stable across servers and match application code using the same hash.
+
+use udf::prelude::*;
+use xxhash_rust::xxh64::{xxh64, Xxh64};
+
+use crate::dispatch::{Algorithm, AnyHasher, ALGORITHM_NAMES};
+use crate::{hash_arg, is_null};
+
+/// Multiplier from the jump consistent hash paper
+const JUMP_MULTIPLIER: u64 = 2_862_933_555_777_941_757;
+
+/// Jump consistent hash (Lamping & Veach), mapping a key to `0..buckets`
+fn jump_consistent_hash(mut key: u64, buckets: u32) -> u32 {
+    let mut b: i64 = -1;
+    let mut j: i64 = 0;
+    while j < i64::from(buckets) {
+        b = j;
+        key = key.wrapping_mul(JUMP_MULTIPLIER).wrapping_add(1);
+        j = ((b + 1) as f64 * ((1u64 << 31) as f64 / ((key >> 33) + 1) as f64)) as i64;
+    }
+    b as u32
+}
+
+/// Get a 64-bit key from an argument. Integers are used directly, anything else is
+/// hashed with xxhash64.
+fn key_u64(arg: SqlArg<Process>) -> Option<u64> {
+    match arg.value() {
+        SqlResult::Int(Some(i)) => Some(i as u64),
+        v if is_null(&v) => None,
+        _ => Some(hash_arg(arg, |buf| xxh64(buf, 0))),
+    }
+}
+
+/// Validate a constant bucket count argument
+fn buckets_init(args: &ArgList<Init>, idx: usize, fn_name: &str) -> Result<(), String> {
+    let mut arg = args.get(idx).unwrap();
+
+    // Values must be checked before setting coercion
+    if let Some(n) = arg.value().as_int() {
+        if !(1..=i64::from(i32::MAX)).contains(&n) {
+            return Err(format!(
+                "{fn_name}: bucket count must be between 1 and {}, got {n}",
+                i32::MAX
+            ));
+        }
+    }
+    arg.set_type_coercion(SqlType::Int);
+    Ok(())
+}
+
+/// Get the bucket count for this row, `None` if it is invalid
+fn buckets_arg(arg: SqlArg<Process>) -> Option<u32> {
+    let n = arg.value().as_int()?;
+    (1..=i64::from(i32::MAX)).contains(&n).then_some(n as u32)
+}
+
+/// `jump_hash(key, buckets)`
+struct JumpHash;
+
+#[register(name = "jump_hash")]
+impl BasicUdf for JumpHash {
+    type Returns<'a> = Option<i64>;
+
+    fn init(_cfg: &UdfCfg<Init>, args: &ArgList<Init>) -> Result<Self, String> {
+        if args.len() != 2 {
+            return Err(format!(
+                "jump_hash takes 2 arguments but got {} (usage: `jump_hash(key, buckets)`)",
+                args.len()
+            ));
+        }
+        buckets_init(args, 1, "jump_hash")?;
+        Ok(Self)
+    }
+
+    fn process<'a>(
+        &'a mut self,
+        _cfg: &UdfCfg<Process>,
+        args: &ArgList<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<Self::Returns<'a>, ProcessError> {
+        let (Some(key), Some(buckets)) = (
+            key_u64(args.get(0).unwrap()),
+            buckets_arg(args.get(1).unwrap()),
+        ) else {
+            return Ok(None);
+        };
+        Ok(Some(jump_consistent_hash(key, buckets).into()))
+    }
+}
+
+/// `rendezvous_hash(key, node...)`, returning the node with the highest score
+struct RendezvousHash {
+    ret: Vec<u8>,
+}
+
+#[register(name = "rendezvous_hash")]
+impl BasicUdf for RendezvousHash {
+    type Returns<'a> = Option<&'a [u8]>;
+
+    fn init(_cfg: &UdfCfg<Init>, args: &ArgList<Init>) -> Result<Self, String> {
+        if args.len() < 2 {
+            return Err(format!(
+                "rendezvous_hash takes at least 2 arguments but got {} \
+                (usage: `rendezvous_hash(key, node...)`)",
+                args.len()
+            ));
+        }
+        args.iter()
+            .skip(1)
+            .for_each(|mut arg| arg.set_type_coercion(SqlType::String));
+        Ok(Self { ret: Vec::new() })
+    }
+
+    fn process<'a>(
+        &'a mut self,
+        _cfg: &UdfCfg<Process>,
+        args: &ArgList<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<Self::Returns<'a>, ProcessError> {
+        let key = args.get(0).unwrap();
+        if is_null(&key.value()) {
+            return Ok(None);
+        }
+        let seed = hash_arg(key, |buf| xxh64(buf, 0));
+
+        // Score each node using the key's hash as the seed. `NULL` nodes are
+        // skipped, and the first node wins ties.
+        let mut best: Option<(u64, &[u8])> = None;
+        let values: Vec<_> = args.iter().skip(1).map(|arg| arg.value()).collect();
+        for node in values.iter().filter_map(SqlResult::as_bytes) {
+            let score = xxh64(node, seed);
+            if best.map_or(true, |(best_score, _)| score > best_score) {
+                best = Some((score, node));
+            }
+        }
+
+        let Some((_, node)) = best else {
+            return Ok(None);
+        };
+        self.ret.clear();
+        self.ret.extend_from_slice(node);
+        Ok(Some(&self.ret))
+    }
+}
+
+/// `hash_bucket(algorithm, key, n)`, the hash modulo `n`
+struct HashBucket {
+    /// Set if the algorithm is constant
+    fixed: bool,
+    /// The most recently used algorithm, reused if the next row matches
+    current: Option<(Algorithm, AnyHasher)>,
+    buf: Vec<u8>,
+}
+
+#[register(name = "hash_bucket")]
+impl BasicUdf for HashBucket {
+    type Returns<'a> = Option<i64>;
+
+    fn init(_cfg: &UdfCfg<Init>, args: &ArgList<Init>) -> Result<Self, String> {
+        if args.len() != 3 {
+            return Err(format!(
+                "hash_bucket takes 3 arguments but got {} \
+                (usage: `hash_bucket(algorithm, key, n)`)",
+                args.len()
+            ));
+        }
+
+        let mut algo_arg = args.get(0).unwrap();
+        let current = match algo_arg.value().as_bytes() {
+            Some(name) => {
+                let algo = Algorithm::from_name(name).ok_or_else(|| {
+                    format!(
+                        "hash_bucket: unknown algorithm '{}'; expected one of {ALGORITHM_NAMES}",
+                        String::from_utf8_lossy(name)
+                    )
+                })?;
+                Some((algo, algo.hasher()))
+            }
+            None => None,
+        };
+        algo_arg.set_type_coercion(SqlType::String);
+        buckets_init(args, 2, "hash_bucket")?;
+
+        Ok(Self {
+            fixed: current.is_some(),
+            current,
+            buf: Vec::new(),
+        })
+    }
+
+    fn process<'a>(
+        &'a mut self,
+        _cfg: &UdfCfg<Process>,
+        args: &ArgList<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<Self::Returns<'a>, ProcessError> {
+        if !self.fixed {
+            let algo_arg = args.get(0).unwrap().value();
+            let Some(algo) = algo_arg.as_bytes().and_then(Algorithm::from_name) else {
+                return Ok(None);
+            };
+            if !matches!(self.current, Some((cur, _)) if cur == algo) {
+                self.current = Some((algo, algo.hasher()));
+            }
+        }
+
+        let key = args.get(1).unwrap();
+        let (Some((_, hasher)), Some(n)) =
+            (self.current.as_mut(), buckets_arg(args.get(2).unwrap()))
+        else {
+            return Ok(None);
+        };
+        if is_null(&key.value()) {
+            return Ok(None);
+        }
+
+        hash_arg(key, |buf| hasher.update(buf));
+        hasher.finalize_into_reset(&mut self.buf);
+        Ok(Some((digest_prefix_u64(&self.buf) % u64::from(n)) as i64))
+    }
+}
+
+/// Read up to the first 8 bytes of a digest as a big endian integer. For xxhash
+/// this is the hash value itself.
+fn digest_prefix_u64(digest: &[u8]) -> u64 {
+    digest
+        .iter()
+        .take(8)
+        .fold(0u64, |acc, b| (acc << 8) | u64::from(*b))
+}
+
+/// `hash_to_unit(data...)`, a uniform double in `[0, 1)`
+struct HashToUnit;
+
+#[register(name = "hash_to_unit")]
+impl BasicUdf for HashToUnit {
+    type Returns<'a> = f64;
+
+    fn init(_cfg: &UdfCfg<Init>, _args: &ArgList<Init>) -> Result<Self, String> {
+        Ok(Self)
+    }
+
+    fn process<'a>(
+        &'a mut self,
+        _cfg: &UdfCfg<Process>,
+        args: &ArgList<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<Self::Returns<'a>, ProcessError> {
+        let mut hasher = Xxh64::new(0);
+        args.iter()
+            .for_each(|arg| hash_arg(arg, |buf| hasher.update(buf)));
+        Ok(unit_f64(hasher.digest()))
+    }
+}
+
+/// Map a hash to `[0, 1)` using its top 53 bits, the precision of an `f64`
+fn unit_f64(hash: u64) -> f64 {
+    (hash >> 11) as f64 * (1.0 / (1u64 << 53) as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_jump_consistent_hash() {
+        // Vectors from the reference implementations
+        assert_eq!(jump_consistent_hash(1, 1), 0);
+        assert_eq!(jump_consistent_hash(42, 57), 43);
+        assert_eq!(jump_consistent_hash(0xDEAD10CC, 1), 0);
+        assert_eq!(jump_consistent_hash(0xDEAD10CC, 666), 361);
+        assert_eq!(jump_consistent_hash(256, 1024), 520);
+
+        // Growing the bucket count only moves keys to the new bucket
+        for key in 0..1000 {
+            let before = jump_consistent_hash(key, 10);
+            let after = jump_consistent_hash(key, 11);
+            assert!(after == before || after == 10);
+        }
+    }
+
+    #[test]
+    fn test_unit_f64() {
+        assert_eq!(unit_f64(0), 0.0);
+        assert!(unit_f64(u64::MAX) < 1.0);
+        assert_eq!(unit_f64(1 << 63), 0.5);
+    }
+
+    #[test]
+    fn test_digest_prefix() {
+        assert_eq!(digest_prefix_u64(&[0x12, 0x34]), 0x1234);
+        assert_eq!(
+            digest_prefix_u64(&[1, 2, 3, 4, 5, 6, 7, 8, 9]),
+            0x0102_0304_0506_0708
+        );
+    }
+}
@@ -0,0 +1,448 @@
+//! Generic `hash(algorithm, data...)` that selects the hasher by name
+//!
+//! If the algorithm is a constant, it is resolved once in `init` and an unknown name
+//! is an error. Otherwise it is resolved for each row, and rows with an unknown
+//! algorithm return `NULL`.
+//!
+//! The xxhash algorithms produce their digest as big endian bytes (the canonical
+//! xxhash representation), so `hash('xxhash64', x)` is the hex of `xxhash64(x)`.
+//!
+//! `hash_canonical(algorithm, data...)` is the same, except that numbers are hashed
+//! as normalized decimal text (see `hash_arg_canonical`). This gives equal values
+//! from `DECIMAL`, `DOUBLE`, and integer columns the same digest.
+
+use digest::DynDigest;
+use udf::prelude::*;
+use xxhash_rust::xxh3::Xxh3;
+use xxhash_rust::xxh32::Xxh32;
+use xxhash_rust::xxh64::Xxh64;
+
+use crate::{hash_arg, hash_arg_canonical};
+
+/// Longest output of any algorithm, used for `set_max_len`
+const MAX_HASH_LEN: usize = 64;
+
+/// Every algorithm available to `hash`, named the same as its UDF
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Algorithm {
+    Blake2b512,
+    Blake2s256,
+    Blake3,
+    Md5,
+    Sha1,
+    Sha224,
+    Sha256,
+    Sha384,
+    Sha512,
+    Sha512_224,
+    Sha512_256,
+    Keccak224,
+    Keccak256,
+    Sha3_224,
+    Sha3_256,
+    Sha3_384,
+    Sha3_512,
+    Ripemd160,
+    Sm3,
+    Streebog256,
+    Streebog512,
+    Whirlpool,
+    XxHash3,
+    XxHash32,
+    XxHash64,
+}
+
+impl Algorithm {
+    /// Names are matched case-insensitively. `md5` and `sha1` are accepted as well
+    /// as the `_u` names of their UDFs.
+    pub(crate) fn from_name(name: &[u8]) -> Option<Self> {
+        let ret = match name.to_ascii_lowercase().as_slice() {
+            b"blake2b512" => Self::Blake2b512,
+            b"blake2s256" => Self::Blake2s256,
+            b"blake3" => Self::Blake3,
+            b"md5" | b"md5_u" => Self::Md5,
+            b"sha1" | b"sha1_u" => Self::Sha1,
+            b"sha224" => Self::Sha224,
+            b"sha256" => Self::Sha256,
+            b"sha384" => Self::Sha384,
+            b"sha512" => Self::Sha512,
+            b"sha512_224" => Self::Sha512_224,
+            b"sha512_256" => Self::Sha512_256,
+            b"keccak224" => Self::Keccak224,
+            b"keccak256" => Self::Keccak256,
+            b"sha3_224" => Self::Sha3_224,
+            b"sha3_256" => Self::Sha3_256,
+            b"sha3_384" => Self::Sha3_384,
+            b"sha3_512" => Self::Sha3_512,
+            b"ripemd160" => Self::Ripemd160,
+            b"sm3" => Self::Sm3,
+            b"streebog256" => Self::Streebog256,
+            b"streebog512" => Self::Streebog512,
+            b"whirlpool" => Self::Whirlpool,
+            b"xxhash3" => Self::XxHash3,
+            b"xxhash32" => Self::XxHash32,
+            b"xxhash64" | b"xxhash" => Self::XxHash64,
+            _ => return None,
+        };
+        Some(ret)
+    }
+
+    pub(crate) fn hasher(self) -> AnyHasher {
+        fn dyn_digest<D: DynDigest + Default + 'static>() -> AnyHasher {
+            AnyHasher::Digest(Box::<D>::default())
+        }
+
+        match self {
+            Self::Blake2b512 => dyn_digest::<blake2::Blake2b512>(),
+            Self::Blake2s256 => dyn_digest::<blake2::Blake2s256>(),
+            Self::Blake3 => AnyHasher::Blake3(Box::default()),
+            Self::Md5 => dyn_digest::<md5::Md5>(),
+            Self::Sha1 => dyn_digest::<sha1::Sha1>(),
+            Self::Sha224 => dyn_digest::<sha2::Sha224>(),
+            Self::Sha256 => dyn_digest::<sha2::Sha256>(),
+            Self::Sha384 => dyn_digest::<sha2::Sha384>(),
+            Self::Sha512 => dyn_digest::<sha2::Sha512>(),
+            Self::Sha512_224 => dyn_digest::<sha2::Sha512_224>(),
+            Self::Sha512_256 => dyn_digest::<sha2::Sha512_256>(),
+            Self::Keccak224 => dyn_digest::<sha3::Keccak224>(),
+            Self::Keccak256 => dyn_digest::<sha3::Keccak256>(),
+            Self::Sha3_224 => dyn_digest::<sha3::Sha3_224>(),
+            Self::Sha3_256 => dyn_digest::<sha3::Sha3_256>(),
+            Self::Sha3_384 => dyn_digest::<sha3::Sha3_384>(),
+            Self::Sha3_512 => dyn_digest::<sha3::Sha3_512>(),
+            Self::Ripemd160 => dyn_digest::<ripemd::Ripemd160>(),
+            Self::Sm3 => dyn_digest::<sm3::Sm3>(),
+            Self::Streebog256 => dyn_digest::<streebog::Streebog256>(),
+            Self::Streebog512 => dyn_digest::<streebog::Streebog512>(),
+            Self::Whirlpool => dyn_digest::<whirlpool::Whirlpool>(),
+            Self::XxHash3 => AnyHasher::XxHash3(Box::default()),
+            Self::XxHash32 => AnyHasher::XxHash32(Xxh32::new(0)),
+            Self::XxHash64 => AnyHasher::XxHash64(Xxh64::new(0)),
+        }
+    }
+}
+
+pub(crate) const ALGORITHM_NAMES: &str =
+    "blake2b512, blake2s256, blake3, md5, sha1, sha224, sha256, \
+    sha384, sha512, sha512_224, sha512_256, keccak224, keccak256, sha3_224, sha3_256, \
+    sha3_384, sha3_512, ripemd160, sm3, streebog256, streebog512, whirlpool, xxhash3, \
+    xxhash32, xxhash64";
+
+/// One of the hashers used by the dedicated UDFs. Large hashers are boxed to
+/// keep the enum small.
+pub(crate) enum AnyHasher {
+    Digest(Box<dyn DynDigest>),
+    Blake3(Box<blake3::Hasher>),
+    XxHash3(Box<Xxh3>),
+    XxHash32(Xxh32),
+    XxHash64(Xxh64),
+}
+
+impl AnyHasher {
+    pub(crate) fn update(&mut self, buf: &[u8]) {
+        match self {
+            Self::Digest(h) => h.update(buf),
+            Self::Blake3(h) => {
+                h.update(buf);
+            }
+            Self::XxHash3(h) => h.update(buf),
+            Self::XxHash32(h) => h.update(buf),
+            Self::XxHash64(h) => h.update(buf),
+        }
+    }
+
+    /// Write the digest to `out` and reset the hasher
+    pub(crate) fn finalize_into_reset(&mut self, out: &mut Vec<u8>) {
+        out.clear();
+        match self {
+            Self::Digest(h) => {
+                out.resize(h.output_size(), 0);
+                h.finalize_into_reset(out).unwrap();
+            }
+            Self::Blake3(h) => {
+                out.extend_from_slice(h.finalize().as_bytes());
+                h.reset();
+            }
+            Self::XxHash3(h) => {
+                out.extend_from_slice(&h.digest().to_be_bytes());
+                h.reset();
+            }
+            Self::XxHash32(h) => {
+                out.extend_from_slice(&h.digest().to_be_bytes());
+                h.reset(0);
+            }
+            Self::XxHash64(h) => {
+                out.extend_from_slice(&h.digest().to_be_bytes());
+                h.reset(0);
+            }
+        }
+    }
+}
+
+/// Shared implementation for `hash` and `hash_bin`
+struct HashDispatch {
+    hex: bool,
+    /// Normalize numbers before hashing
+    canonical: bool,
+    /// Set if the algorithm is constant
+    fixed: bool,
+    /// The most recently used algorithm, reused if the next row matches
+    current: Option<(Algorithm, AnyHasher)>,
+    ret: Vec<u8>,
+    hex_ret: Vec<u8>,
+}
+
+impl HashDispatch {
+    fn init(
+        cfg: &UdfCfg<Init>,
+        args: &ArgList<Init>,
+        fn_name: &str,
+        hex: bool,
+        canonical: bool,
+    ) -> Result<Self, String> {
+        let Some(mut first) = args.get(0) else {
+            return Err(format!(
+                "{fn_name} takes at least 1 argument but got 0 (usage: `{fn_name}(algorithm, data...)`)"
+            ));
+        };
+
+        let current = match first.value().as_bytes() {
+            Some(name) => {
+                let algo = Algorithm::from_name(name).ok_or_else(|| {
+                    format!(
+                        "{fn_name}: unknown algorithm '{}'; expected one of {ALGORITHM_NAMES}",
+                        String::from_utf8_lossy(name)
+                    )
+                })?;
+                Some((algo, algo.hasher()))
+            }
+            None => None,
+        };
+        first.set_type_coercion(SqlType::String);
+
+        let max_len = if hex { MAX_HASH_LEN * 2 } else { MAX_HASH_LEN };
+        cfg.set_max_len(max_len as u64);
+
+        Ok(Self {
+            hex,
+            canonical,
+            fixed: current.is_some(),
+            current,
+            ret: Vec::new(),
+            hex_ret: Vec::new(),
+        })
+    }
+
+    fn process(&mut self, args: &ArgList<Process>) -> Option<&[u8]> {
+        if !self.fixed {
+            let algo = Algorithm::from_name(args.get(0).unwrap().value().as_bytes()?)?;
+            if !matches!(self.current, Some((cur, _)) if cur == algo) {
+                self.current = Some((algo, algo.hasher()));
+            }
+        }
+
+        let (_, hasher) = self.current.as_mut()?;
+        if self.canonical {
+            args.iter()
+                .skip(1)
+                .for_each(|arg| hash_arg_canonical(arg, |buf| hasher.update(buf)));
+        } else {
+            args.iter()
+                .skip(1)
+                .for_each(|arg| hash_arg(arg, |buf| hasher.update(buf)));
+        }
+        hasher.finalize_into_reset(&mut self.ret);
+
+        if !self.hex {
+            return Some(&self.ret);
+        }
+
+        self.hex_ret.resize(self.ret.len() * 2, 0);
+        data_encoding::HEXUPPER.encode_mut(&self.ret, &mut self.hex_ret);
+        Some(&self.hex_ret)
+    }
+}
+
+/// Hash with a named algorithm, returning hex
+struct Hash {
+    inner: HashDispatch,
+}
+
+#[register(name = "hash")]
+impl BasicUdf for Hash {
+    type Returns<'a> = Option<&'a [u8]>;
+
+    fn init(cfg: &UdfCfg<Init>, args: &ArgList<Init>) -> Result<Self, String> {
+        HashDispatch::init(cfg, args, "hash", true, false).map(|inner| Self { inner })
+    }
+
+    fn process<'a>(
+        &'a mut self,
+        _cfg: &UdfCfg<Process>,
+        args: &ArgList<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<Self::Returns<'a>, ProcessError> {
+        Ok(self.inner.process(args))
+    }
+}
+
+/// Hash with a named algorithm, returning binary
+struct HashBin {
+    inner: HashDispatch,
+}
+
+#[register(name = "hash_bin")]
+impl BasicUdf for HashBin {
+    type Returns<'a> = Option<&'a [u8]>;
+
+    fn init(cfg: &UdfCfg<Init>, args: &ArgList<Init>) -> Result<Self, String> {
+        HashDispatch::init(cfg, args, "hash_bin", false, false).map(|inner| Self { inner })
+    }
+
+    fn process<'a>(
+        &'a mut self,
+        _cfg: &UdfCfg<Process>,
+        args: &ArgList<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<Self::Returns<'a>, ProcessError> {
+        Ok(self.inner.process(args))
+    }
+}
+
+/// Hash normalized numbers with a named algorithm, returning hex
+struct HashCanonical {
+    inner: HashDispatch,
+}
+
+#[register(name = "hash_canonical")]
+impl BasicUdf for HashCanonical {
+    type Returns<'a> = Option<&'a [u8]>;
+
+    fn init(cfg: &UdfCfg<Init>, args: &ArgList<Init>) -> Result<Self, String> {
+        HashDispatch::init(cfg, args, "hash_canonical", true, true).map(|inner| Self { inner })
+    }
+
+    fn process<'a>(
+        &'a mut self,
+        _cfg: &UdfCfg<Process>,
+        args: &ArgList<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<Self::Returns<'a>, ProcessError> {
+        Ok(self.inner.process(args))
+    }
+}
+
+/// Hash normalized numbers with a named algorithm, returning binary
+struct HashCanonicalBin {
+    inner: HashDispatch,
+}
+
+#[register(name = "hash_canonical_bin")]
+impl BasicUdf for HashCanonicalBin {
+    type Returns<'a> = Option<&'a [u8]>;
+
+    fn init(cfg: &UdfCfg<Init>, args: &ArgList<Init>) -> Result<Self, String> {
+        HashDispatch::init(cfg, args, "hash_canonical_bin", false, true).map(|inner| Self { inner })
+    }
+
+    fn process<'a>(
+        &'a mut self,
+        _cfg: &UdfCfg<Process>,
+        args: &ArgList<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<Self::Returns<'a>, ProcessError> {
+        Ok(self.inner.process(args))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use udf::mock::*;
+    use xxhash_rust::xxh64::xxh64;
+
+    use super::*;
+
+    #[test]
+    fn test_hash_fixed() {
+        let mut cfg = MockUdfCfg::new();
+        let mut args = mock_args![
+            ("SHA256", "", false),
+            ("Hello, ", "", false),
+            ("world!", "", false)
+        ];
+
+        let mut udf = Hash::init(cfg.as_init(), args.as_init()).unwrap();
+        let res = udf
+            .process(cfg.as_process(), args.as_process(), None)
+            .unwrap();
+        assert_eq!(
+            res.unwrap(),
+            b"315F5BDB76D078C43B8AC0064E4A0164612B1FCE77C869345BFC94C75894EDD3"
+        );
+
+        let mut args = mock_args![("sha256", "", false), ("x", "", false)];
+        assert!(Hash::init(cfg.as_init(), args.as_init()).is_ok());
+
+        let mut args = mock_args![("sha9000", "", false), ("x", "", false)];
+        assert!(Hash::init(cfg.as_init(), args.as_init())
+            .err()
+            .unwrap()
+            .starts_with("hash: unknown algorithm 'sha9000'"));
+    }
+
+    #[test]
+    fn test_hash_per_row() {
+        let mut cfg = MockUdfCfg::new();
+        let mut init_args = mock_args![(String None, "", false), ("", "", false)];
+        let mut udf = HashBin::init(cfg.as_init(), init_args.as_init()).unwrap();
+
+        let mut args = mock_args![("xxhash64", "", false), ("Hello, world!", "", false)];
+        let res = udf
+            .process(cfg.as_process(), args.as_process(), None)
+            .unwrap();
+        assert_eq!(res.unwrap(), xxh64(b"Hello, world!", 0).to_be_bytes());
+
+        let mut args = mock_args![("blake3", "", false), ("Hello, world!", "", false)];
+        let res = udf
+            .process(cfg.as_process(), args.as_process(), None)
+            .unwrap();
+        assert_eq!(res.unwrap(), blake3::hash(b"Hello, world!").as_bytes());
+
+        let mut args = mock_args![("nope", "", false), ("Hello, world!", "", false)];
+        let res = udf
+            .process(cfg.as_process(), args.as_process(), None)
+            .unwrap();
+        assert_eq!(res, None);
+    }
+
+    #[test]
+    fn test_hash_canonical() {
+        let mut cfg = MockUdfCfg::new();
+        let mut hash = |mut args: MockArgList| {
+            let mut udf = HashCanonicalBin::init(cfg.as_init(), args.as_init()).unwrap();
+            let res = udf
+                .process(cfg.as_process(), args.as_process(), None)
+                .unwrap();
+            res.unwrap().to_owned()
+        };
+
+        let expected = blake3::hash(b"1.5").as_bytes().to_vec();
+        assert_eq!(
+            hash(mock_args![("blake3", "", false), (Decimal "1.50", "", false)]),
+            expected
+        );
+        assert_eq!(
+            hash(mock_args![("blake3", "", false), (1.5_f64, "", false)]),
+            expected
+        );
+
+        assert_eq!(
+            hash(mock_args![("blake3", "", false), (Int 2, "", false)]),
+            hash(mock_args![("blake3", "", false), (Decimal "2.000", "", false)])
+        );
+        assert_eq!(
+            hash(mock_args![("blake3", "", false), (-0.0_f64, "", false)]),
+            hash(mock_args![("blake3", "", false), (0.0_f64, "", false)])
+        );
+    }
+}
@@ -0,0 +1,218 @@
+//! Encode binary hash output as text
+//!
+//! The hex functions always return uppercase hex. `hash_encode` allows converting
+//! any `_bin` result to another representation without a `LOWER(HEX(...))` round
+//! trip, e.g. `hash_encode(sha256_bin(x), 'base64url')`.
+
+use data_encoding::{Encoding, Specification};
+use once_cell::sync::OnceCell;
+use udf::prelude::*;
+
+/// Text encodings available for hash output
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum OutputEncoding {
+    /// Lowercase hex, matching `sha2()` and most other languages
+    HexLower,
+    /// Uppercase hex, matching the hex functions in this library
+    HexUpper,
+    /// Standard base64 with padding
+    Base64,
+    /// URL-safe base64 without padding
+    Base64Url,
+    /// RFC 4648 base32 with padding
+    Base32,
+    /// Crockford's base32 without padding
+    Crockford,
+}
+
+impl OutputEncoding {
+    const ALL: [Self; 6] = [
+        Self::HexLower,
+        Self::HexUpper,
+        Self::Base64,
+        Self::Base64Url,
+        Self::Base32,
+        Self::Crockford,
+    ];
+
+    /// Names are matched case-insensitively
+    fn from_name(name: &[u8]) -> Option<Self> {
+        let ret = match name.to_ascii_lowercase().as_slice() {
+            b"hex" | b"hexlower" => Self::HexLower,
+            b"hexupper" => Self::HexUpper,
+            b"base64" | b"b64" => Self::Base64,
+            b"base64url" | b"b64url" => Self::Base64Url,
+            b"base32" | b"b32" => Self::Base32,
+            b"crockford" | b"base32crockford" => Self::Crockford,
+            _ => return None,
+        };
+        Some(ret)
+    }
+
+    fn encoding(self) -> &'static Encoding {
+        // `Encoding` constants can't be borrowed for `'static`, so copy them to statics
+        static HEXLOWER: Encoding = data_encoding::HEXLOWER;
+        static HEXUPPER: Encoding = data_encoding::HEXUPPER;
+        static BASE64: Encoding = data_encoding::BASE64;
+        static BASE64URL_NOPAD: Encoding = data_encoding::BASE64URL_NOPAD;
+        static BASE32: Encoding = data_encoding::BASE32;
+
+        match self {
+            Self::HexLower => &HEXLOWER,
+            Self::HexUpper => &HEXUPPER,
+            Self::Base64 => &BASE64,
+            Self::Base64Url => &BASE64URL_NOPAD,
+            Self::Base32 => &BASE32,
+            Self::Crockford => crockford(),
+        }
+    }
+
+    /// Length of `len` bytes once encoded
+    fn encoded_len(self, len: u64) -> u64 {
+        // Server lengths fit in 32 bits, and clamping keeps `encode_len` from
+        // overflowing
+        let len = len.min(u32::MAX.into()) as usize;
+        self.encoding().encode_len(len) as u64
+    }
+}
+
+/// Crockford's base32 is not provided by `data_encoding`, so build it once
+fn crockford() -> &'static Encoding {
+    static CROCKFORD: OnceCell<Encoding> = OnceCell::new();
+
+    CROCKFORD.get_or_init(|| {
+        let mut spec = Specification::new();
+        spec.symbols.push_str("0123456789ABCDEFGHJKMNPQRSTVWXYZ");
+        spec.encoding().unwrap()
+    })
+}
+
+const ENCODING_NAMES: &str = "hex, hexupper, base64, base64url, base32, crockford";
+
+/// Encode a binary string with a named encoding
+#[derive(Debug)]
+struct HashEncode {
+    /// Set if the encoding is constant
+    encoding: Option<OutputEncoding>,
+    ret: String,
+}
+
+#[register(name = "hash_encode")]
+impl BasicUdf for HashEncode {
+    type Returns<'a> = Option<&'a str>;
+
+    fn init(cfg: &UdfCfg<Init>, args: &ArgList<Init>) -> Result<Self, String> {
+        if args.len() != 2 {
+            return Err(format!(
+                "hash_encode takes 2 arguments but got {} (usage: `hash_encode(data, encoding)`)",
+                args.len()
+            ));
+        }
+
+        let mut enc_arg = args.get(1).unwrap();
+        let encoding = match enc_arg.value().as_bytes() {
+            Some(name) => Some(OutputEncoding::from_name(name).ok_or_else(|| {
+                format!(
+                    "hash_encode: unknown encoding '{}'; expected one of {ENCODING_NAMES}",
+                    String::from_utf8_lossy(name)
+                )
+            })?),
+            None => None,
+        };
+
+        // The default max length is that of the longest argument, but encoded
+        // output is longer than its input
+        let input_len = cfg.get_max_len();
+        let max_len = match encoding {
+            Some(enc) => enc.encoded_len(input_len),
+            None => OutputEncoding::ALL
+                .iter()
+                .map(|enc| enc.encoded_len(input_len))
+                .max()
+                .unwrap(),
+        };
+        cfg.set_max_len(max_len);
+
+        args.get(0).unwrap().set_type_coercion(SqlType::String);
+        enc_arg.set_type_coercion(SqlType::String);
+
+        Ok(Self {
+            encoding,
+            ret: String::new(),
+        })
+    }
+
+    fn process<'a>(
+        &'a mut self,
+        _cfg: &UdfCfg<Process>,
+        args: &ArgList<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<Self::Returns<'a>, ProcessError> {
+        let input = args.get(0).unwrap().value();
+        let Some(data) = input.as_bytes() else {
+            return Ok(None);
+        };
+
+        let encoding = match self.encoding {
+            Some(enc) => enc,
+            None => {
+                let enc_arg = args.get(1).unwrap().value();
+                let Some(enc) = enc_arg.as_bytes().and_then(OutputEncoding::from_name) else {
+                    return Ok(None);
+                };
+                enc
+            }
+        };
+
+        self.ret.clear();
+        encoding.encoding().encode_append(data, &mut self.ret);
+        Ok(Some(&self.ret))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use udf::mock::*;
+
+    use super::*;
+
+    #[test]
+    fn test_encodings() {
+        let input = b"Hello, world!";
+        let enc = |name: &str| {
+            OutputEncoding::from_name(name.as_bytes())
+                .unwrap()
+                .encoding()
+                .encode(input)
+        };
+
+        assert_eq!(enc("hex"), "48656c6c6f2c20776f726c6421");
+        assert_eq!(enc("HEXUPPER"), "48656C6C6F2C20776F726C6421");
+        assert_eq!(enc("base64"), "SGVsbG8sIHdvcmxkIQ==");
+        assert_eq!(enc("b64url"), "SGVsbG8sIHdvcmxkIQ");
+        assert_eq!(enc("base32"), "JBSWY3DPFQQHO33SNRSCC===");
+        assert_eq!(enc("crockford"), "91JPRV3F5GG7EVVJDHJ22");
+        assert_eq!(OutputEncoding::from_name(b"base58"), None);
+    }
+
+    #[test]
+    fn test_max_len() {
+        let mut cfg = MockUdfCfg::new();
+        let mut init_len = |encoding: Option<&str>| {
+            *cfg.max_len() = 32;
+            let mut args = match encoding {
+                Some(enc) => mock_args![(String None, "", false), (enc, "", false)],
+                None => mock_args![(String None, "", false), (String None, "", false)],
+            };
+            HashEncode::init(cfg.as_init(), args.as_init()).unwrap();
+            *cfg.max_len()
+        };
+
+        assert_eq!(init_len(Some("hex")), 64);
+        assert_eq!(init_len(Some("base64")), 44);
+        assert_eq!(init_len(Some("base32")), 56);
+        assert_eq!(init_len(Some("crockford")), 52);
+        // Per-row encodings need room for the longest
+        assert_eq!(init_len(None), 64);
+    }
+}
@@ -0,0 +1,396 @@
+//! HyperLogLog distinct-count sketches
+//!
+//! `hll_agg(col[, precision])` builds a sketch from a column, `hll_merge_agg(sketch)`
+//! unions stored sketches, and `hll_count(sketch)` estimates the number of distinct
+//! values. Values are hashed with xxhash3, the same as `xxhash3(col)`, and `NULL`s
+//! are skipped like they are for `COUNT(DISTINCT col)`.
+//!
+//! A sketch is serialized as a version byte, the precision `p`, then `2^p` one-byte
+//! registers. Sketches of different precisions can be merged; the result has the
+//! lower of the two precisions.
+
+use udf::prelude::*;
+use xxhash_rust::xxh3::xxh3_64;
+
+use crate::{hash_arg, is_null};
+
+/// Version byte at the start of every serialized sketch
+const SKETCH_VERSION: u8 = 1;
+/// Length of the header before the registers
+const HEADER_LEN: usize = 2;
+const MIN_PRECISION: u8 = 4;
+const MAX_PRECISION: u8 = 18;
+/// 16 KiB sketches with a standard error of about 0.8%
+const DEFAULT_PRECISION: u8 = 14;
+
+/// A dense HyperLogLog sketch
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Sketch {
+    precision: u8,
+    registers: Vec<u8>,
+}
+
+impl Sketch {
+    fn new(precision: u8) -> Self {
+        Self {
+            precision,
+            registers: vec![0; 1 << precision],
+        }
+    }
+
+    /// Add a 64-bit hash. The top `p` bits select the register, and the register
+    /// keeps the highest rank (leading zeros plus one) of the remaining bits.
+    fn insert_hash(&mut self, hash: u64) {
+        let p = u32::from(self.precision);
+        let idx = (hash >> (64 - p)) as usize;
+        // Set a guard bit so the rank is at most `64 - p + 1`
+        let rest = (hash << p) | (1 << (p - 1));
+        let rank = rest.leading_zeros() as u8 + 1;
+        let reg = &mut self.registers[idx];
+        *reg = (*reg).max(rank);
+    }
+
+    /// Union another sketch into this one, reducing our precision if needed
+    fn merge(&mut self, other: &Self) {
+        if other.precision < self.precision {
+            *self = self.fold(other.precision);
+        }
+        if other.precision > self.precision {
+            self.merge_registers(&other.fold(self.precision).registers);
+        } else {
+            self.merge_registers(&other.registers);
+        }
+    }
+
+    fn merge_registers(&mut self, other: &[u8]) {
+        self.registers
+            .iter_mut()
+            .zip(other)
+            .for_each(|(a, b)| *a = (*a).max(*b));
+    }
+
+    /// Convert to a lower precision. The bits dropped from each index become the
+    /// leading bits of the rest of the hash, so this gives the same sketch as
+    /// inserting at the lower precision in the first place.
+    fn fold(&self, precision: u8) -> Self {
+        let shift = u32::from(self.precision - precision);
+        let mut ret = Self::new(precision);
+        for (idx, &reg) in self.registers.iter().enumerate() {
+            if reg == 0 {
+                continue;
+            }
+            let dropped = (idx & ((1 << shift) - 1)) as u32;
+            let rank = if dropped == 0 {
+                reg + shift as u8
+            } else {
+                (shift - (32 - dropped.leading_zeros())) as u8 + 1
+            };
+            let new_reg = &mut ret.registers[idx >> shift];
+            *new_reg = (*new_reg).max(rank);
+        }
+        ret
+    }
+
+    /// Estimate the cardinality, using linear counting for small ranges
+    fn estimate(&self) -> f64 {
+        let m = self.registers.len() as f64;
+        let alpha = match self.registers.len() {
+            16 => 0.673,
+            32 => 0.697,
+            64 => 0.709,
+            _ => 0.7213 / (1.0 + 1.079 / m),
+        };
+        let sum: f64 = self
+            .registers
+            .iter()
+            .map(|&r| 2f64.powi(-i32::from(r)))
+            .sum();
+        let raw = alpha * m * m / sum;
+
+        let zeros = self.registers.iter().filter(|&&r| r == 0).count();
+        if raw <= 2.5 * m && zeros > 0 {
+            m * (m / zeros as f64).ln()
+        } else {
+            raw
+        }
+    }
+
+    fn serialize_into(&self, out: &mut Vec<u8>) {
+        out.clear();
+        out.reserve(HEADER_LEN + self.registers.len());
+        out.extend_from_slice(&[SKETCH_VERSION, self.precision]);
+        out.extend_from_slice(&self.registers);
+    }
+
+    /// Parse a serialized sketch, `None` if it is not valid
+    fn deserialize(buf: &[u8]) -> Option<Self> {
+        if buf.len() < HEADER_LEN {
+            return None;
+        }
+        let (header, registers) = buf.split_at(HEADER_LEN);
+        let (version, precision) = (header[0], header[1]);
+        if version != SKETCH_VERSION
+            || !(MIN_PRECISION..=MAX_PRECISION).contains(&precision)
+            || registers.len() != 1 << precision
+            || registers.iter().any(|&r| r > 64 - precision + 1)
+        {
+            return None;
+        }
+        Some(Self {
+            precision,
+            registers: registers.to_owned(),
+        })
+    }
+}
+
+/// `hll_agg(col[, precision])`, building a sketch from every non-null value
+struct HllAgg {
+    sketch: Sketch,
+    ret: Vec<u8>,
+}
+
+#[register(name = "hll_agg")]
+impl BasicUdf for HllAgg {
+    type Returns<'a> = &'a [u8];
+
+    fn init(cfg: &UdfCfg<Init>, args: &ArgList<Init>) -> Result<Self, String> {
+        if !(1..=2).contains(&args.len()) {
+            return Err(format!(
+                "hll_agg takes 1 or 2 arguments but got {} (usage: `hll_agg(col[, precision])`)",
+                args.len()
+            ));
+        }
+
+        let precision = match args.get(1) {
+            Some(mut arg) => {
+                let Some(p) = arg.value().as_int() else {
+                    return Err("hll_agg: precision must be a constant integer".to_owned());
+                };
+                if !(i64::from(MIN_PRECISION)..=i64::from(MAX_PRECISION)).contains(&p) {
+                    return Err(format!(
+                        "hll_agg: precision must be between {MIN_PRECISION} and \
+                        {MAX_PRECISION}, got {p}"
+                    ));
+                }
+                arg.set_type_coercion(SqlType::Int);
+                p as u8
+            }
+            None => DEFAULT_PRECISION,
+        };
+
+        cfg.set_max_len((HEADER_LEN + (1 << precision)) as u64);
+        Ok(Self {
+            sketch: Sketch::new(precision),
+            ret: Vec::new(),
+        })
+    }
+
+    fn process<'a>(
+        &'a mut self,
+        _cfg: &UdfCfg<Process>,
+        _args: &ArgList<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<Self::Returns<'a>, ProcessError> {
+        self.sketch.serialize_into(&mut self.ret);
+        Ok(&self.ret)
+    }
+}
+
+#[register(name = "hll_agg")]
+impl AggregateUdf for HllAgg {
+    fn clear(
+        &mut self,
+        _cfg: &UdfCfg<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<(), NonZeroU8> {
+        self.sketch.registers.fill(0);
+        Ok(())
+    }
+
+    fn add(
+        &mut self,
+        _cfg: &UdfCfg<Process>,
+        args: &ArgList<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<(), NonZeroU8> {
+        let arg = args.get(0).unwrap();
+        if !is_null(&arg.value()) {
+            self.sketch.insert_hash(hash_arg(arg, xxh3_64));
+        }
+        Ok(())
+    }
+}
+
+/// `hll_merge_agg(sketch)`, the union of every non-null sketch. Any invalid sketch
+/// makes the result `NULL`.
+struct HllMergeAgg {
+    sketch: Option<Sketch>,
+    invalid: bool,
+    ret: Vec<u8>,
+}
+
+#[register(name = "hll_merge_agg")]
+impl BasicUdf for HllMergeAgg {
+    type Returns<'a> = Option<&'a [u8]>;
+
+    fn init(cfg: &UdfCfg<Init>, args: &ArgList<Init>) -> Result<Self, String> {
+        if args.len() != 1 {
+            return Err(format!(
+                "hll_merge_agg takes 1 argument but got {} (usage: `hll_merge_agg(sketch)`)",
+                args.len()
+            ));
+        }
+        args.get(0).unwrap().set_type_coercion(SqlType::String);
+        cfg.set_max_len((HEADER_LEN + (1 << MAX_PRECISION)) as u64);
+        Ok(Self {
+            sketch: None,
+            invalid: false,
+            ret: Vec::new(),
+        })
+    }
+
+    fn process<'a>(
+        &'a mut self,
+        _cfg: &UdfCfg<Process>,
+        _args: &ArgList<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<Self::Returns<'a>, ProcessError> {
+        if self.invalid {
+            return Ok(None);
+        }
+        let Some(sketch) = &self.sketch else {
+            return Ok(None);
+        };
+        sketch.serialize_into(&mut self.ret);
+        Ok(Some(&self.ret))
+    }
+}
+
+#[register(name = "hll_merge_agg")]
+impl AggregateUdf for HllMergeAgg {
+    fn clear(
+        &mut self,
+        _cfg: &UdfCfg<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<(), NonZeroU8> {
+        self.sketch = None;
+        self.invalid = false;
+        Ok(())
+    }
+
+    fn add(
+        &mut self,
+        _cfg: &UdfCfg<Process>,
+        args: &ArgList<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<(), NonZeroU8> {
+        let value = args.get(0).unwrap().value();
+        let Some(buf) = value.as_bytes() else {
+            return Ok(());
+        };
+        let Some(other) = Sketch::deserialize(buf) else {
+            self.invalid = true;
+            return Ok(());
+        };
+        match &mut self.sketch {
+            Some(sketch) => sketch.merge(&other),
+            None => self.sketch = Some(other),
+        }
+        Ok(())
+    }
+}
+
+/// `hll_count(sketch)`, the estimated number of distinct values
+struct HllCount;
+
+#[register(name = "hll_count")]
+impl BasicUdf for HllCount {
+    type Returns<'a> = Option<i64>;
+
+    fn init(_cfg: &UdfCfg<Init>, args: &ArgList<Init>) -> Result<Self, String> {
+        if args.len() != 1 {
+            return Err(format!(
+                "hll_count takes 1 argument but got {} (usage: `hll_count(sketch)`)",
+                args.len()
+            ));
+        }
+        args.get(0).unwrap().set_type_coercion(SqlType::String);
+        Ok(Self)
+    }
+
+    fn process<'a>(
+        &'a mut self,
+        _cfg: &UdfCfg<Process>,
+        args: &ArgList<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<Self::Returns<'a>, ProcessError> {
+        let estimate = args
+            .get(0)
+            .unwrap()
+            .value()
+            .as_bytes()
+            .and_then(Sketch::deserialize)
+            .map(|sketch| sketch.estimate().round() as i64);
+        Ok(estimate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sketch_of(precision: u8, values: impl Iterator<Item = u64>) -> Sketch {
+        let mut sketch = Sketch::new(precision);
+        values.for_each(|v| sketch.insert_hash(xxh3_64(&v.to_le_bytes())));
+        sketch
+    }
+
+    #[test]
+    fn test_estimate() {
+        assert_eq!(Sketch::new(DEFAULT_PRECISION).estimate(), 0.0);
+
+        for n in [1, 10, 1000, 100_000] {
+            let sketch = sketch_of(DEFAULT_PRECISION, 0..n);
+            let err = (sketch.estimate() - n as f64).abs() / n as f64;
+            assert!(err < 0.02, "n={n} estimate={}", sketch.estimate());
+        }
+
+        // Duplicates do not change the estimate
+        let a = sketch_of(DEFAULT_PRECISION, 0..1000);
+        let b = sketch_of(DEFAULT_PRECISION, (0..1000).chain(0..1000));
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut a = sketch_of(12, 0..5000);
+        let b = sketch_of(12, 2500..10_000);
+        a.merge(&b);
+        assert_eq!(a, sketch_of(12, 0..10_000));
+
+        // Merging a lower precision folds down to it
+        let mut a = sketch_of(14, 0..5000);
+        let b = sketch_of(10, 5000..10_000);
+        a.merge(&b);
+        assert_eq!(a, sketch_of(10, 0..10_000));
+
+        let mut a = sketch_of(8, 0..5000);
+        let b = sketch_of(16, 5000..10_000);
+        a.merge(&b);
+        assert_eq!(a, sketch_of(8, 0..10_000));
+    }
+
+    #[test]
+    fn test_serialize() {
+        let sketch = sketch_of(MIN_PRECISION, 0..100);
+        let mut buf = Vec::new();
+        sketch.serialize_into(&mut buf);
+        assert_eq!(buf.len(), HEADER_LEN + 16);
+        assert_eq!(Sketch::deserialize(&buf), Some(sketch));
+
+        assert_eq!(Sketch::deserialize(&[]), None);
+        assert_eq!(Sketch::deserialize(&buf[..buf.len() - 1]), None);
+        buf[0] = 2;
+        assert_eq!(Sketch::deserialize(&buf), None);
+    }
+}
@@ -32,114 +32,655 @@
 //! CREATE FUNCTION sha3_384_hex RETURNS string SONAME 'libudf_hash.so';
 //! CREATE FUNCTION sha3_512 RETURNS string SONAME 'libudf_hash.so';
 //! CREATE FUNCTION sha3_512_hex RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION sha512_224 RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION sha512_224_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION sha512_256 RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION sha512_256_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION ripemd160 RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION ripemd160_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION sm3 RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION sm3_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION streebog256 RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION streebog256_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION streebog512 RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION streebog512_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION whirlpool RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION whirlpool_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION shake128 RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION shake128_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION shake256 RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION shake256_bin RETURNS string SONAME 'libudf_hash.so';
+//!
+//! -- NIST SP 800-185, e.g. `kmac256(key, customization, length, data...)`
+//! CREATE FUNCTION tuplehash128 RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION tuplehash128_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION tuplehash256 RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION tuplehash256_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION kmac128 RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION kmac128_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION kmac256 RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION kmac256_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION cshake256 RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION cshake256_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION k12 RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION k12_bin RETURNS string SONAME 'libudf_hash.so';
+//!
 //! CREATE FUNCTION xxhash RETURNS integer SONAME 'libudf_hash.so';
 //! CREATE FUNCTION xxhash3 RETURNS integer SONAME 'libudf_hash.so';
 //! CREATE FUNCTION xxhash32 RETURNS integer SONAME 'libudf_hash.so';
 //! CREATE FUNCTION xxhash64 RETURNS integer SONAME 'libudf_hash.so';
+//! CREATE FUNCTION xxhash128 RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION xxhash128_bin RETURNS string SONAME 'libudf_hash.so';
+//!
+//! -- seeded xxhash, usage is `xxhash64_seed(seed, data...)`
+//! CREATE FUNCTION xxhash_seed RETURNS integer SONAME 'libudf_hash.so';
+//! CREATE FUNCTION xxhash3_seed RETURNS integer SONAME 'libudf_hash.so';
+//! CREATE FUNCTION xxhash32_seed RETURNS integer SONAME 'libudf_hash.so';
+//! CREATE FUNCTION xxhash64_seed RETURNS integer SONAME 'libudf_hash.so';
+//! CREATE FUNCTION xxhash3_secret RETURNS integer SONAME 'libudf_hash.so';
+//!
+//! -- fast checksums and non-cryptographic hashes
+//! CREATE FUNCTION crc32c RETURNS integer SONAME 'libudf_hash.so';
+//! CREATE FUNCTION crc64_nvme RETURNS integer SONAME 'libudf_hash.so';
+//! CREATE FUNCTION fnv1a_32 RETURNS integer SONAME 'libudf_hash.so';
+//! CREATE FUNCTION fnv1a_64 RETURNS integer SONAME 'libudf_hash.so';
+//! CREATE FUNCTION murmur3_32 RETURNS integer SONAME 'libudf_hash.so';
+//! CREATE FUNCTION murmur3_128 RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION murmur3_128_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION siphash24 RETURNS integer SONAME 'libudf_hash.so';
+//! CREATE FUNCTION highwayhash64 RETURNS integer SONAME 'libudf_hash.so';
+//!
+//! -- unsigned xxhash, returned as a decimal
+//! CREATE FUNCTION xxhash_u RETURNS decimal SONAME 'libudf_hash.so';
+//! CREATE FUNCTION xxhash3_u RETURNS decimal SONAME 'libudf_hash.so';
+//! CREATE FUNCTION xxhash64_u RETURNS decimal SONAME 'libudf_hash.so';
+//!
+//! -- framed hashing, where argument boundaries, types and NULLs all affect the result
+//! CREATE FUNCTION blake2b512_tuple RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION blake2b512_tuple_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION blake2s256_tuple RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION blake2s256_tuple_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION blake3_tuple RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION blake3_tuple_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION md5_u_tuple RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION md5_u_tuple_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION sha1_u_tuple RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION sha1_u_tuple_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION sha224_tuple RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION sha224_tuple_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION sha256_tuple RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION sha256_tuple_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION sha384_tuple RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION sha384_tuple_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION sha512_tuple RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION sha512_tuple_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION keccak224_tuple RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION keccak224_tuple_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION keccak256_tuple RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION keccak256_tuple_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION sha3_224_tuple RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION sha3_224_tuple_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION sha3_256_tuple RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION sha3_256_tuple_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION sha3_384_tuple RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION sha3_384_tuple_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION sha3_512_tuple RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION sha3_512_tuple_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION sha512_224_tuple RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION sha512_224_tuple_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION sha512_256_tuple RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION sha512_256_tuple_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION ripemd160_tuple RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION ripemd160_tuple_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION sm3_tuple RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION sm3_tuple_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION streebog256_tuple RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION streebog256_tuple_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION streebog512_tuple RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION streebog512_tuple_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION whirlpool_tuple RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION whirlpool_tuple_bin RETURNS string SONAME 'libudf_hash.so';
+//!
+//! -- aggregate versions hash every row in a group
+//! CREATE AGGREGATE FUNCTION blake2b512_agg RETURNS string SONAME 'libudf_hash.so';
+//! CREATE AGGREGATE FUNCTION blake2b512_agg_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE AGGREGATE FUNCTION blake2s256_agg RETURNS string SONAME 'libudf_hash.so';
+//! CREATE AGGREGATE FUNCTION blake2s256_agg_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE AGGREGATE FUNCTION blake3_agg RETURNS string SONAME 'libudf_hash.so';
+//! CREATE AGGREGATE FUNCTION blake3_agg_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE AGGREGATE FUNCTION md5_u_agg RETURNS string SONAME 'libudf_hash.so';
+//! CREATE AGGREGATE FUNCTION md5_u_agg_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE AGGREGATE FUNCTION sha1_u_agg RETURNS string SONAME 'libudf_hash.so';
+//! CREATE AGGREGATE FUNCTION sha1_u_agg_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE AGGREGATE FUNCTION sha224_agg RETURNS string SONAME 'libudf_hash.so';
+//! CREATE AGGREGATE FUNCTION sha224_agg_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE AGGREGATE FUNCTION sha256_agg RETURNS string SONAME 'libudf_hash.so';
+//! CREATE AGGREGATE FUNCTION sha256_agg_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE AGGREGATE FUNCTION sha384_agg RETURNS string SONAME 'libudf_hash.so';
+//! CREATE AGGREGATE FUNCTION sha384_agg_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE AGGREGATE FUNCTION sha512_agg RETURNS string SONAME 'libudf_hash.so';
+//! CREATE AGGREGATE FUNCTION sha512_agg_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE AGGREGATE FUNCTION keccak224_agg RETURNS string SONAME 'libudf_hash.so';
+//! CREATE AGGREGATE FUNCTION keccak224_agg_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE AGGREGATE FUNCTION keccak256_agg RETURNS string SONAME 'libudf_hash.so';
+//! CREATE AGGREGATE FUNCTION keccak256_agg_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE AGGREGATE FUNCTION sha3_224_agg RETURNS string SONAME 'libudf_hash.so';
+//! CREATE AGGREGATE FUNCTION sha3_224_agg_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE AGGREGATE FUNCTION sha3_256_agg RETURNS string SONAME 'libudf_hash.so';
+//! CREATE AGGREGATE FUNCTION sha3_256_agg_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE AGGREGATE FUNCTION sha3_384_agg RETURNS string SONAME 'libudf_hash.so';
+//! CREATE AGGREGATE FUNCTION sha3_384_agg_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE AGGREGATE FUNCTION sha3_512_agg RETURNS string SONAME 'libudf_hash.so';
+//! CREATE AGGREGATE FUNCTION sha3_512_agg_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE AGGREGATE FUNCTION sha512_224_agg RETURNS string SONAME 'libudf_hash.so';
+//! CREATE AGGREGATE FUNCTION sha512_224_agg_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE AGGREGATE FUNCTION sha512_256_agg RETURNS string SONAME 'libudf_hash.so';
+//! CREATE AGGREGATE FUNCTION sha512_256_agg_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE AGGREGATE FUNCTION ripemd160_agg RETURNS string SONAME 'libudf_hash.so';
+//! CREATE AGGREGATE FUNCTION ripemd160_agg_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE AGGREGATE FUNCTION sm3_agg RETURNS string SONAME 'libudf_hash.so';
+//! CREATE AGGREGATE FUNCTION sm3_agg_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE AGGREGATE FUNCTION streebog256_agg RETURNS string SONAME 'libudf_hash.so';
+//! CREATE AGGREGATE FUNCTION streebog256_agg_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE AGGREGATE FUNCTION streebog512_agg RETURNS string SONAME 'libudf_hash.so';
+//! CREATE AGGREGATE FUNCTION streebog512_agg_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE AGGREGATE FUNCTION whirlpool_agg RETURNS string SONAME 'libudf_hash.so';
+//! CREATE AGGREGATE FUNCTION whirlpool_agg_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE AGGREGATE FUNCTION xxhash_agg RETURNS integer SONAME 'libudf_hash.so';
+//! CREATE AGGREGATE FUNCTION xxhash3_agg RETURNS integer SONAME 'libudf_hash.so';
+//! CREATE AGGREGATE FUNCTION xxhash32_agg RETURNS integer SONAME 'libudf_hash.so';
+//! CREATE AGGREGATE FUNCTION xxhash64_agg RETURNS integer SONAME 'libudf_hash.so';
+//!
+//! -- order-independent aggregates
+//! CREATE AGGREGATE FUNCTION blake3_set_agg RETURNS string SONAME 'libudf_hash.so';
+//! CREATE AGGREGATE FUNCTION blake3_set_agg_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE AGGREGATE FUNCTION xxhash_set_agg RETURNS integer SONAME 'libudf_hash.so';
+//! CREATE AGGREGATE FUNCTION xxhash64_set_agg RETURNS integer SONAME 'libudf_hash.so';
+//!
+//! -- HMAC, usage is `hmac_sha256(key, data...)`
+//! CREATE FUNCTION hmac_blake2b512 RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION hmac_blake2b512_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION hmac_blake2s256 RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION hmac_blake2s256_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION hmac_sha1 RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION hmac_sha1_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION hmac_md5 RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION hmac_md5_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION hmac_sha224 RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION hmac_sha224_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION hmac_sha256 RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION hmac_sha256_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION hmac_sha384 RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION hmac_sha384_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION hmac_sha512 RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION hmac_sha512_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION hmac_keccak224 RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION hmac_keccak224_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION hmac_keccak256 RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION hmac_keccak256_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION hmac_sha3_224 RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION hmac_sha3_224_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION hmac_sha3_256 RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION hmac_sha3_256_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION hmac_sha3_384 RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION hmac_sha3_384_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION hmac_sha3_512 RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION hmac_sha3_512_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION hmac_sha512_224 RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION hmac_sha512_224_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION hmac_sha512_256 RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION hmac_sha512_256_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION hmac_ripemd160 RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION hmac_ripemd160_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION hmac_sm3 RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION hmac_sm3_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION hmac_streebog256 RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION hmac_streebog256_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION hmac_streebog512 RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION hmac_streebog512_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION hmac_whirlpool RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION hmac_whirlpool_bin RETURNS string SONAME 'libudf_hash.so';
+//!
+//! -- blake3 modes: `blake3_keyed(key, data...)`, `blake3_derive_key(context, data...)`,
+//! -- and `blake3_xof(length, data...)`
+//! CREATE FUNCTION blake3_keyed RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION blake3_keyed_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION blake3_keyed_thd RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION blake3_keyed_thd_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION blake3_derive_key RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION blake3_derive_key_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION blake3_derive_key_thd RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION blake3_derive_key_thd_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION blake3_xof RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION blake3_xof_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION blake3_xof_thd RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION blake3_xof_thd_bin RETURNS string SONAME 'libudf_hash.so';
+//!
+//! -- encode binary output, e.g. `hash_encode(sha256_bin(x), 'base64url')`
+//! CREATE FUNCTION hash_encode RETURNS string SONAME 'libudf_hash.so';
+//!
+//! -- select the algorithm by name, e.g. `hash('sha3_256', data...)`
+//! CREATE FUNCTION hash RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION hash_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION hash_canonical RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION hash_canonical_bin RETURNS string SONAME 'libudf_hash.so';
+//!
+//! -- consistent hashing, bucketing, and sampling
+//! CREATE FUNCTION jump_hash RETURNS integer SONAME 'libudf_hash.so';
+//! CREATE FUNCTION rendezvous_hash RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION hash_bucket RETURNS integer SONAME 'libudf_hash.so';
+//! CREATE FUNCTION hash_to_unit RETURNS real SONAME 'libudf_hash.so';
+//!
+//! -- HyperLogLog distinct counts, e.g. `hll_count(hll_merge_agg(daily_sketch))`
+//! CREATE AGGREGATE FUNCTION hll_agg RETURNS string SONAME 'libudf_hash.so';
+//! CREATE AGGREGATE FUNCTION hll_merge_agg RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION hll_count RETURNS integer SONAME 'libudf_hash.so';
+//!
+//! -- Bloom filters, e.g. `bloom_contains(bloom_agg(col, 10000, 0.01), value)`
+//! CREATE AGGREGATE FUNCTION bloom_agg RETURNS string SONAME 'libudf_hash.so';
+//! CREATE AGGREGATE FUNCTION bloom_merge_agg RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION bloom_contains RETURNS integer SONAME 'libudf_hash.so';
+//!
+//! -- RFC 6962 Merkle tree roots, usage is `merkle_root_agg(leaf...)`
+//! CREATE AGGREGATE FUNCTION merkle_root_agg RETURNS string SONAME 'libudf_hash.so';
+//! CREATE AGGREGATE FUNCTION merkle_root_agg_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE AGGREGATE FUNCTION merkle_root_blake3_agg RETURNS string SONAME 'libudf_hash.so';
+//! CREATE AGGREGATE FUNCTION merkle_root_blake3_agg_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION merkle_leaf_hash RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION merkle_leaf_hash_bin RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION merkle_leaf_blake3 RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION merkle_leaf_blake3_bin RETURNS string SONAME 'libudf_hash.so';
+//!
+//! -- similarity signatures for near-duplicate detection
+//! CREATE FUNCTION minhash RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION minhash_similarity RETURNS real SONAME 'libudf_hash.so';
+//! CREATE FUNCTION simhash64 RETURNS integer SONAME 'libudf_hash.so';
+//! CREATE FUNCTION hamming_distance RETURNS integer SONAME 'libudf_hash.so';
+//!
+//! -- password hashing and verification
+//! CREATE FUNCTION argon2id_hash RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION bcrypt_hash RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION scrypt_hash RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION pbkdf2_sha256 RETURNS string SONAME 'libudf_hash.so';
+//! CREATE FUNCTION password_verify RETURNS integer SONAME 'libudf_hash.so';
 //! ```
 
-use digest::Digest;
+pub mod blake3_modes;
+pub mod blake3_pool;
+pub mod bloom;
+pub mod bucket;
+pub mod dispatch;
+pub mod encode;
+pub mod hll;
+pub mod merkle;
+pub mod noncrypto;
+pub mod password;
+pub mod set_agg;
+pub mod sha3_xof;
+pub mod similarity;
+pub mod xxhash_ext;
+
+use digest::core_api::BlockSizeUser;
+use digest::{Digest, KeyInit};
+use hmac::{Mac, SimpleHmac};
+use paste::paste;
 use udf::prelude::*;
 use xxhash_rust::xxh3::{xxh3_64, Xxh3};
 use xxhash_rust::xxh32::{xxh32, Xxh32};
 use xxhash_rust::xxh64::{xxh64, Xxh64};
 
-// Make a simple UDF for anything that uses the `Digest` interface
+// Make a simple UDF for anything that uses the `Digest` interface. Each invocation
+// creates a hex function `$fn_name`, a binary `$fn_name_bin`, aggregate versions
+// of both (`$fn_name_agg` and `$fn_name_agg_bin`), framed versions that are
+// unambiguous for multiple arguments (`$fn_name_tuple` and `$fn_name_tuple_bin`),
+// and HMAC functions `$hmac_name` and `$hmac_name_bin`
 macro_rules! digest_udf {
-    ($hash_ty:ty, $fn_name:ident, $bin_fn_name:ident, $hash_len:expr) => {
-        // Provide an implementation that returns a hexified string
-        #[allow(non_camel_case_types)]
-        struct $fn_name {
-            /// The hasher is stored so we can create it once then reset it on each call.
-            hasher: $hash_ty,
-            /// Store our hashed value
-            hashed: [u8; $hash_len],
-            /// Output for our hexified values
-            hex: [u8; $hash_len * 2],
-        }
+    ($hash_ty:ty, $fn_name:ident, $hmac_name:ident, $hash_len:expr) => {
+        paste! {
+            // Provide an implementation that returns a hexified string
+            #[allow(non_camel_case_types)]
+            struct $fn_name {
+                /// The hasher is stored so we can create it once then reset it on each call.
+                hasher: $hash_ty,
+                /// Store our hashed value
+                hashed: [u8; $hash_len],
+                /// Output for our hexified values
+                hex: [u8; $hash_len * 2],
+            }
 
-        #[register]
-        impl BasicUdf for $fn_name {
-            type Returns<'a> = &'a [u8];
-
-            fn init(_cfg: &UdfCfg<Init>, _args: &ArgList<Init>) -> Result<Self, String> {
-                let ret = Self {
-                    hasher: <$hash_ty as digest::Digest>::new(),
-                    hashed: [0u8; $hash_len],
-                    hex: [0u8; $hash_len * 2],
-                };
-                Ok(ret)
+            #[register]
+            impl BasicUdf for $fn_name {
+                type Returns<'a> = &'a [u8];
+
+                fn init(_cfg: &UdfCfg<Init>, _args: &ArgList<Init>) -> Result<Self, String> {
+                    let ret = Self {
+                        hasher: <$hash_ty as digest::Digest>::new(),
+                        hashed: [0u8; $hash_len],
+                        hex: [0u8; $hash_len * 2],
+                    };
+                    Ok(ret)
+                }
+
+                fn process<'a>(
+                    &'a mut self,
+                    _cfg: &UdfCfg<Process>,
+                    args: &ArgList<Process>,
+                    _error: Option<NonZeroU8>,
+                ) -> Result<Self::Returns<'a>, ProcessError> {
+                    args.iter()
+                        .for_each(|arg| hash_arg(arg, |buf| self.hasher.update(buf)));
+                    self.hasher.finalize_into_reset((&mut self.hashed).into());
+                    // Encode our data to hex
+                    data_encoding::HEXUPPER.encode_mut(&self.hashed, &mut self.hex);
+                    Ok(&self.hex)
+                }
             }
 
-            fn process<'a>(
-                &'a mut self,
-                _cfg: &UdfCfg<Process>,
-                args: &ArgList<Process>,
-                _error: Option<NonZeroU8>,
-            ) -> Result<Self::Returns<'a>, ProcessError> {
-                args.iter()
-                    .for_each(|arg| hash_arg(arg, |buf| self.hasher.update(buf)));
-                self.hasher.finalize_into_reset((&mut self.hashed).into());
-                // Encode our data to hex
-                data_encoding::HEXUPPER.encode_mut(&self.hashed, &mut self.hex);
-                Ok(&self.hex)
+            // Provide an implementation that returns a binary string
+            #[allow(non_camel_case_types)]
+            struct [<$fn_name _bin>] {
+                /// The hasher is stored so we can create it once then reset it on each call.
+                /// This seems like it is
+                hasher: $hash_ty,
+                /// Store our return value, we can return a reference to it without allocating
+                ret: [u8; $hash_len],
             }
-        }
 
-        // Provide an implementation that returns a binary string
-        #[allow(non_camel_case_types)]
-        struct $bin_fn_name {
-            /// The hasher is stored so we can create it once then reset it on each call.
-            /// This seems like it is
-            hasher: $hash_ty,
-            /// Store our return value, we can return a reference to it without allocating
-            ret: [u8; $hash_len],
-        }
+            #[register]
+            impl BasicUdf for [<$fn_name _bin>] {
+                type Returns<'a> = &'a [u8];
+
+                fn init(_cfg: &UdfCfg<Init>, _args: &ArgList<Init>) -> Result<Self, String> {
+                    let ret = Self {
+                        hasher: <$hash_ty as digest::Digest>::new(),
+                        ret: [0u8; $hash_len],
+                    };
+                    Ok(ret)
+                }
+
+                fn process<'a>(
+                    &'a mut self,
+                    _cfg: &UdfCfg<Process>,
+                    args: &ArgList<Process>,
+                    _error: Option<NonZeroU8>,
+                ) -> Result<Self::Returns<'a>, ProcessError> {
+                    args.iter()
+                        .for_each(|arg| hash_arg(arg, |buf| self.hasher.update(buf)));
+                    self.hasher.finalize_into_reset((&mut self.ret).into());
+                    Ok(&self.ret)
+                }
+            }
+
+            // Aggregate version: every row in the group is streamed into the same
+            // hasher, and the digest is produced once the group is complete
+            #[allow(non_camel_case_types)]
+            struct [<$fn_name _agg>] {
+                hasher: $hash_ty,
+                hashed: [u8; $hash_len],
+                hex: [u8; $hash_len * 2],
+            }
+
+            #[register]
+            impl BasicUdf for [<$fn_name _agg>] {
+                type Returns<'a> = &'a [u8];
+
+                fn init(_cfg: &UdfCfg<Init>, _args: &ArgList<Init>) -> Result<Self, String> {
+                    let ret = Self {
+                        hasher: <$hash_ty as digest::Digest>::new(),
+                        hashed: [0u8; $hash_len],
+                        hex: [0u8; $hash_len * 2],
+                    };
+                    Ok(ret)
+                }
+
+                fn process<'a>(
+                    &'a mut self,
+                    _cfg: &UdfCfg<Process>,
+                    _args: &ArgList<Process>,
+                    _error: Option<NonZeroU8>,
+                ) -> Result<Self::Returns<'a>, ProcessError> {
+                    self.hasher.finalize_into_reset((&mut self.hashed).into());
+                    data_encoding::HEXUPPER.encode_mut(&self.hashed, &mut self.hex);
+                    Ok(&self.hex)
+                }
+            }
+
+            #[register]
+            impl AggregateUdf for [<$fn_name _agg>] {
+                fn clear(
+                    &mut self,
+                    _cfg: &UdfCfg<Process>,
+                    _error: Option<NonZeroU8>,
+                ) -> Result<(), NonZeroU8> {
+                    Digest::reset(&mut self.hasher);
+                    Ok(())
+                }
+
+                fn add(
+                    &mut self,
+                    _cfg: &UdfCfg<Process>,
+                    args: &ArgList<Process>,
+                    _error: Option<NonZeroU8>,
+                ) -> Result<(), NonZeroU8> {
+                    args.iter()
+                        .for_each(|arg| hash_arg(arg, |buf| self.hasher.update(buf)));
+                    Ok(())
+                }
+            }
+
+            // Aggregate version that returns a binary string
+            #[allow(non_camel_case_types)]
+            struct [<$fn_name _agg_bin>] {
+                hasher: $hash_ty,
+                ret: [u8; $hash_len],
+            }
+
+            #[register]
+            impl BasicUdf for [<$fn_name _agg_bin>] {
+                type Returns<'a> = &'a [u8];
+
+                fn init(_cfg: &UdfCfg<Init>, _args: &ArgList<Init>) -> Result<Self, String> {
+                    let ret = Self {
+                        hasher: <$hash_ty as digest::Digest>::new(),
+                        ret: [0u8; $hash_len],
+                    };
+                    Ok(ret)
+                }
 
-        #[register]
-        impl BasicUdf for $bin_fn_name {
-            type Returns<'a> = &'a [u8];
+                fn process<'a>(
+                    &'a mut self,
+                    _cfg: &UdfCfg<Process>,
+                    _args: &ArgList<Process>,
+                    _error: Option<NonZeroU8>,
+                ) -> Result<Self::Returns<'a>, ProcessError> {
+                    self.hasher.finalize_into_reset((&mut self.ret).into());
+                    Ok(&self.ret)
+                }
+            }
+
+            #[register]
+            impl AggregateUdf for [<$fn_name _agg_bin>] {
+                fn clear(
+                    &mut self,
+                    _cfg: &UdfCfg<Process>,
+                    _error: Option<NonZeroU8>,
+                ) -> Result<(), NonZeroU8> {
+                    Digest::reset(&mut self.hasher);
+                    Ok(())
+                }
+
+                fn add(
+                    &mut self,
+                    _cfg: &UdfCfg<Process>,
+                    args: &ArgList<Process>,
+                    _error: Option<NonZeroU8>,
+                ) -> Result<(), NonZeroU8> {
+                    args.iter()
+                        .for_each(|arg| hash_arg(arg, |buf| self.hasher.update(buf)));
+                    Ok(())
+                }
+            }
 
-            fn init(_cfg: &UdfCfg<Init>, _args: &ArgList<Init>) -> Result<Self, String> {
-                let ret = Self {
-                    hasher: <$hash_ty as digest::Digest>::new(),
-                    ret: [0u8; $hash_len],
-                };
-                Ok(ret)
+            // Tuple version: each argument is framed with its type and length, so
+            // different argument splits and types always produce different input
+            #[allow(non_camel_case_types)]
+            struct [<$fn_name _tuple>] {
+                hasher: $hash_ty,
+                hashed: [u8; $hash_len],
+                hex: [u8; $hash_len * 2],
             }
 
-            fn process<'a>(
-                &'a mut self,
-                _cfg: &UdfCfg<Process>,
-                args: &ArgList<Process>,
-                _error: Option<NonZeroU8>,
-            ) -> Result<Self::Returns<'a>, ProcessError> {
-                args.iter()
-                    .for_each(|arg| hash_arg(arg, |buf| self.hasher.update(buf)));
-                self.hasher.finalize_into_reset((&mut self.ret).into());
-                Ok(&self.ret)
+            #[register]
+            impl BasicUdf for [<$fn_name _tuple>] {
+                type Returns<'a> = &'a [u8];
+
+                fn init(_cfg: &UdfCfg<Init>, _args: &ArgList<Init>) -> Result<Self, String> {
+                    let ret = Self {
+                        hasher: <$hash_ty as digest::Digest>::new(),
+                        hashed: [0u8; $hash_len],
+                        hex: [0u8; $hash_len * 2],
+                    };
+                    Ok(ret)
+                }
+
+                fn process<'a>(
+                    &'a mut self,
+                    _cfg: &UdfCfg<Process>,
+                    args: &ArgList<Process>,
+                    _error: Option<NonZeroU8>,
+                ) -> Result<Self::Returns<'a>, ProcessError> {
+                    args.iter()
+                        .for_each(|arg| hash_arg_framed(arg, |buf| self.hasher.update(buf)));
+                    self.hasher.finalize_into_reset((&mut self.hashed).into());
+                    data_encoding::HEXUPPER.encode_mut(&self.hashed, &mut self.hex);
+                    Ok(&self.hex)
+                }
+            }
+
+            // Tuple version that returns a binary string
+            #[allow(non_camel_case_types)]
+            struct [<$fn_name _tuple_bin>] {
+                hasher: $hash_ty,
+                ret: [u8; $hash_len],
+            }
+
+            #[register]
+            impl BasicUdf for [<$fn_name _tuple_bin>] {
+                type Returns<'a> = &'a [u8];
+
+                fn init(_cfg: &UdfCfg<Init>, _args: &ArgList<Init>) -> Result<Self, String> {
+                    let ret = Self {
+                        hasher: <$hash_ty as digest::Digest>::new(),
+                        ret: [0u8; $hash_len],
+                    };
+                    Ok(ret)
+                }
+
+                fn process<'a>(
+                    &'a mut self,
+                    _cfg: &UdfCfg<Process>,
+                    args: &ArgList<Process>,
+                    _error: Option<NonZeroU8>,
+                ) -> Result<Self::Returns<'a>, ProcessError> {
+                    args.iter()
+                        .for_each(|arg| hash_arg_framed(arg, |buf| self.hasher.update(buf)));
+                    self.hasher.finalize_into_reset((&mut self.ret).into());
+                    Ok(&self.ret)
+                }
+            }
+
+            // HMAC using this hash. The first argument is the key, all others are data
+            #[allow(non_camel_case_types)]
+            struct $hmac_name {
+                hashed: [u8; $hash_len],
+                hex: [u8; $hash_len * 2],
+            }
+
+            #[register]
+            impl BasicUdf for $hmac_name {
+                type Returns<'a> = Option<&'a [u8]>;
+
+                fn init(_cfg: &UdfCfg<Init>, args: &ArgList<Init>) -> Result<Self, String> {
+                    hmac_init(args, stringify!($hmac_name))?;
+                    let ret = Self {
+                        hashed: [0u8; $hash_len],
+                        hex: [0u8; $hash_len * 2],
+                    };
+                    Ok(ret)
+                }
+
+                fn process<'a>(
+                    &'a mut self,
+                    _cfg: &UdfCfg<Process>,
+                    args: &ArgList<Process>,
+                    _error: Option<NonZeroU8>,
+                ) -> Result<Self::Returns<'a>, ProcessError> {
+                    if !hmac_process::<$hash_ty>(args, &mut self.hashed) {
+                        return Ok(None);
+                    }
+                    data_encoding::HEXUPPER.encode_mut(&self.hashed, &mut self.hex);
+                    Ok(Some(&self.hex))
+                }
+            }
+
+            // HMAC that returns a binary string
+            #[allow(non_camel_case_types)]
+            struct [<$hmac_name _bin>] {
+                ret: [u8; $hash_len],
+            }
+
+            #[register]
+            impl BasicUdf for [<$hmac_name _bin>] {
+                type Returns<'a> = Option<&'a [u8]>;
+
+                fn init(_cfg: &UdfCfg<Init>, args: &ArgList<Init>) -> Result<Self, String> {
+                    hmac_init(args, stringify!([<$hmac_name _bin>]))?;
+                    Ok(Self {
+                        ret: [0u8; $hash_len],
+                    })
+                }
+
+                fn process<'a>(
+                    &'a mut self,
+                    _cfg: &UdfCfg<Process>,
+                    args: &ArgList<Process>,
+                    _error: Option<NonZeroU8>,
+                ) -> Result<Self::Returns<'a>, ProcessError> {
+                    if !hmac_process::<$hash_ty>(args, &mut self.ret) {
+                        return Ok(None);
+                    }
+                    Ok(Some(&self.ret))
+                }
             }
         }
     };
 }
 
-digest_udf!(blake2::Blake2b512, blake2b512, blake2b512_bin, 64);
-digest_udf!(blake2::Blake2s256, blake2s256, blake2s256_bin, 32);
-digest_udf!(sha1::Sha1, sha1_u, sha1_u_bin, 20);
-digest_udf!(md5::Md5, md5_u, md5_u_bin, 16);
-digest_udf!(sha2::Sha224, sha224, sha224_bin, 28);
-digest_udf!(sha2::Sha256, sha256, sha256_bin, 32);
-digest_udf!(sha2::Sha384, sha384, sha384_bin, 48);
-digest_udf!(sha2::Sha512, sha512, sha512_bin, 64);
-digest_udf!(sha3::Keccak224, keccak224, keccak224_bin, 28);
-digest_udf!(sha3::Keccak256, keccak256, keccak256_bin, 32);
-digest_udf!(sha3::Sha3_224, sha3_224, sha3_224_bin, 28);
-digest_udf!(sha3::Sha3_256, sha3_256, sha3_256_bin, 32);
-digest_udf!(sha3::Sha3_384, sha3_384, sha3_384_bin, 48);
-digest_udf!(sha3::Sha3_512, sha3_512, sha3_512_bin, 64);
+digest_udf!(blake2::Blake2b512, blake2b512, hmac_blake2b512, 64);
+digest_udf!(blake2::Blake2s256, blake2s256, hmac_blake2s256, 32);
+digest_udf!(sha1::Sha1, sha1_u, hmac_sha1, 20);
+digest_udf!(md5::Md5, md5_u, hmac_md5, 16);
+digest_udf!(sha2::Sha224, sha224, hmac_sha224, 28);
+digest_udf!(sha2::Sha256, sha256, hmac_sha256, 32);
+digest_udf!(sha2::Sha384, sha384, hmac_sha384, 48);
+digest_udf!(sha2::Sha512, sha512, hmac_sha512, 64);
+digest_udf!(sha2::Sha512_224, sha512_224, hmac_sha512_224, 28);
+digest_udf!(sha2::Sha512_256, sha512_256, hmac_sha512_256, 32);
+digest_udf!(sha3::Keccak224, keccak224, hmac_keccak224, 28);
+digest_udf!(sha3::Keccak256, keccak256, hmac_keccak256, 32);
+digest_udf!(sha3::Sha3_224, sha3_224, hmac_sha3_224, 28);
+digest_udf!(sha3::Sha3_256, sha3_256, hmac_sha3_256, 32);
+digest_udf!(sha3::Sha3_384, sha3_384, hmac_sha3_384, 48);
+digest_udf!(sha3::Sha3_512, sha3_512, hmac_sha3_512, 64);
+digest_udf!(ripemd::Ripemd160, ripemd160, hmac_ripemd160, 20);
+digest_udf!(::sm3::Sm3, sm3, hmac_sm3, 32);
+digest_udf!(streebog::Streebog256, streebog256, hmac_streebog256, 32);
+digest_udf!(streebog::Streebog512, streebog512, hmac_streebog512, 64);
+digest_udf!(::whirlpool::Whirlpool, whirlpool, hmac_whirlpool, 64);
 
 // Blake3 is special and doesn't implement `Digest` :). We also provide a threaded
-// implementation.
+// implementation, and the plain version switches to threads for large arguments
+// (see `blake3_pool`).
 struct Blake3 {
     hasher: blake3::Hasher,
     hashed: [u8; 32],
@@ -167,7 +708,7 @@ impl BasicUdf for Blake3 {
     ) -> Result<Self::Returns<'a>, ProcessError> {
         args.iter().for_each(|arg| {
             hash_arg(arg, |buf| {
-                self.hasher.update(buf);
+                blake3_pool::update_auto(&mut self.hasher, buf);
             })
         });
         let hash = self.hasher.finalize();
@@ -203,7 +744,7 @@ impl BasicUdf for Blake3Bin {
     ) -> Result<Self::Returns<'a>, ProcessError> {
         args.iter().for_each(|arg| {
             hash_arg(arg, |buf| {
-                self.hasher.update(buf);
+                blake3_pool::update_auto(&mut self.hasher, buf);
             })
         });
         let hash = self.hasher.finalize();
@@ -213,6 +754,78 @@ impl BasicUdf for Blake3Bin {
     }
 }
 
+/// Blake3 with framed arguments, see `hash_arg_framed`
+struct Blake3Tuple {
+    hasher: blake3::Hasher,
+    hashed: [u8; 32],
+    hex: [u8; 64],
+}
+
+#[register(name = "blake3_tuple")]
+impl BasicUdf for Blake3Tuple {
+    type Returns<'a> = &'a [u8];
+
+    fn init(_cfg: &UdfCfg<Init>, _args: &ArgList<Init>) -> Result<Self, String> {
+        let ret = Self {
+            hasher: blake3::Hasher::new(),
+            hashed: [0u8; 32],
+            hex: [0u8; 64],
+        };
+        Ok(ret)
+    }
+
+    fn process<'a>(
+        &'a mut self,
+        _cfg: &UdfCfg<Process>,
+        args: &ArgList<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<Self::Returns<'a>, ProcessError> {
+        args.iter().for_each(|arg| {
+            hash_arg_framed(arg, |buf| {
+                self.hasher.update(buf);
+            })
+        });
+        self.hashed = self.hasher.finalize().into();
+        self.hasher.reset();
+        data_encoding::HEXUPPER.encode_mut(&self.hashed, &mut self.hex);
+        Ok(&self.hex)
+    }
+}
+
+struct Blake3TupleBin {
+    hasher: blake3::Hasher,
+    ret: [u8; 32],
+}
+
+#[register(name = "blake3_tuple_bin")]
+impl BasicUdf for Blake3TupleBin {
+    type Returns<'a> = &'a [u8];
+
+    fn init(_cfg: &UdfCfg<Init>, _args: &ArgList<Init>) -> Result<Self, String> {
+        let ret = Self {
+            hasher: blake3::Hasher::new(),
+            ret: [0u8; 32],
+        };
+        Ok(ret)
+    }
+
+    fn process<'a>(
+        &'a mut self,
+        _cfg: &UdfCfg<Process>,
+        args: &ArgList<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<Self::Returns<'a>, ProcessError> {
+        args.iter().for_each(|arg| {
+            hash_arg_framed(arg, |buf| {
+                self.hasher.update(buf);
+            })
+        });
+        self.ret = self.hasher.finalize().into();
+        self.hasher.reset();
+        Ok(&self.ret)
+    }
+}
+
 struct Blake3Thd {
     hasher: blake3::Hasher,
     hashed: [u8; 32],
@@ -240,7 +853,7 @@ impl BasicUdf for Blake3Thd {
     ) -> Result<Self::Returns<'a>, ProcessError> {
         args.iter().for_each(|arg| {
             hash_arg(arg, |buf| {
-                self.hasher.update_rayon(buf);
+                blake3_pool::update_threaded(&mut self.hasher, buf);
             })
         });
         let hash = self.hasher.finalize();
@@ -276,7 +889,7 @@ impl BasicUdf for Blake3ThdBin {
     ) -> Result<Self::Returns<'a>, ProcessError> {
         args.iter().for_each(|arg| {
             hash_arg(arg, |buf| {
-                self.hasher.update_rayon(buf);
+                blake3_pool::update_threaded(&mut self.hasher, buf);
             })
         });
         let hash = self.hasher.finalize();
@@ -286,6 +899,120 @@ impl BasicUdf for Blake3ThdBin {
     }
 }
 
+/// Aggregate blake3, hashing every row in the group
+struct Blake3Agg {
+    hasher: blake3::Hasher,
+    hashed: [u8; 32],
+    hex: [u8; 64],
+}
+
+#[register(name = "blake3_agg")]
+impl BasicUdf for Blake3Agg {
+    type Returns<'a> = &'a [u8];
+
+    fn init(_cfg: &UdfCfg<Init>, _args: &ArgList<Init>) -> Result<Self, String> {
+        let ret = Self {
+            hasher: blake3::Hasher::new(),
+            hashed: [0u8; 32],
+            hex: [0u8; 64],
+        };
+        Ok(ret)
+    }
+
+    fn process<'a>(
+        &'a mut self,
+        _cfg: &UdfCfg<Process>,
+        _args: &ArgList<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<Self::Returns<'a>, ProcessError> {
+        self.hashed = self.hasher.finalize().into();
+        self.hasher.reset();
+        data_encoding::HEXUPPER.encode_mut(&self.hashed, &mut self.hex);
+        Ok(&self.hex)
+    }
+}
+
+#[register(name = "blake3_agg")]
+impl AggregateUdf for Blake3Agg {
+    fn clear(
+        &mut self,
+        _cfg: &UdfCfg<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<(), NonZeroU8> {
+        self.hasher.reset();
+        Ok(())
+    }
+
+    fn add(
+        &mut self,
+        _cfg: &UdfCfg<Process>,
+        args: &ArgList<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<(), NonZeroU8> {
+        args.iter().for_each(|arg| {
+            hash_arg(arg, |buf| {
+                self.hasher.update(buf);
+            })
+        });
+        Ok(())
+    }
+}
+
+struct Blake3AggBin {
+    hasher: blake3::Hasher,
+    ret: [u8; 32],
+}
+
+#[register(name = "blake3_agg_bin")]
+impl BasicUdf for Blake3AggBin {
+    type Returns<'a> = &'a [u8];
+
+    fn init(_cfg: &UdfCfg<Init>, _args: &ArgList<Init>) -> Result<Self, String> {
+        let ret = Self {
+            hasher: blake3::Hasher::new(),
+            ret: [0u8; 32],
+        };
+        Ok(ret)
+    }
+
+    fn process<'a>(
+        &'a mut self,
+        _cfg: &UdfCfg<Process>,
+        _args: &ArgList<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<Self::Returns<'a>, ProcessError> {
+        self.ret = self.hasher.finalize().into();
+        self.hasher.reset();
+        Ok(&self.ret)
+    }
+}
+
+#[register(name = "blake3_agg_bin")]
+impl AggregateUdf for Blake3AggBin {
+    fn clear(
+        &mut self,
+        _cfg: &UdfCfg<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<(), NonZeroU8> {
+        self.hasher.reset();
+        Ok(())
+    }
+
+    fn add(
+        &mut self,
+        _cfg: &UdfCfg<Process>,
+        args: &ArgList<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<(), NonZeroU8> {
+        args.iter().for_each(|arg| {
+            hash_arg(arg, |buf| {
+                self.hasher.update(buf);
+            })
+        });
+        Ok(())
+    }
+}
+
 struct XxHash3;
 struct XxHash32;
 struct XxHash64;
@@ -365,6 +1092,167 @@ impl BasicUdf for XxHash64 {
     }
 }
 
+/// Aggregate xxhash3, xxhash32 and xxhash64. These keep a streaming hasher for the
+/// group rather than using the one-shot functions.
+struct XxHash3Agg(Xxh3);
+struct XxHash32Agg(Xxh32);
+struct XxHash64Agg(Xxh64);
+
+#[register(name = "xxhash3_agg")]
+impl BasicUdf for XxHash3Agg {
+    type Returns<'a> = i64;
+
+    fn init(_cfg: &UdfCfg<Init>, _args: &ArgList<Init>) -> Result<Self, String> {
+        Ok(Self(Xxh3::new()))
+    }
+
+    fn process<'a>(
+        &'a mut self,
+        _cfg: &UdfCfg<Process>,
+        _args: &ArgList<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<Self::Returns<'a>, ProcessError> {
+        Ok(self.0.digest() as i64)
+    }
+}
+
+#[register(name = "xxhash3_agg")]
+impl AggregateUdf for XxHash3Agg {
+    fn clear(
+        &mut self,
+        _cfg: &UdfCfg<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<(), NonZeroU8> {
+        self.0.reset();
+        Ok(())
+    }
+
+    fn add(
+        &mut self,
+        _cfg: &UdfCfg<Process>,
+        args: &ArgList<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<(), NonZeroU8> {
+        args.iter()
+            .for_each(|arg| hash_arg(arg, |buf| self.0.update(buf)));
+        Ok(())
+    }
+}
+
+#[register(name = "xxhash32_agg")]
+impl BasicUdf for XxHash32Agg {
+    type Returns<'a> = i64;
+
+    fn init(_cfg: &UdfCfg<Init>, _args: &ArgList<Init>) -> Result<Self, String> {
+        Ok(Self(Xxh32::new(0)))
+    }
+
+    fn process<'a>(
+        &'a mut self,
+        _cfg: &UdfCfg<Process>,
+        _args: &ArgList<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<Self::Returns<'a>, ProcessError> {
+        Ok(self.0.digest().into())
+    }
+}
+
+#[register(name = "xxhash32_agg")]
+impl AggregateUdf for XxHash32Agg {
+    fn clear(
+        &mut self,
+        _cfg: &UdfCfg<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<(), NonZeroU8> {
+        self.0.reset(0);
+        Ok(())
+    }
+
+    fn add(
+        &mut self,
+        _cfg: &UdfCfg<Process>,
+        args: &ArgList<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<(), NonZeroU8> {
+        args.iter()
+            .for_each(|arg| hash_arg(arg, |buf| self.0.update(buf)));
+        Ok(())
+    }
+}
+
+#[register(name = "xxhash64_agg", alias = "xxhash_agg")]
+impl BasicUdf for XxHash64Agg {
+    type Returns<'a> = i64;
+
+    fn init(_cfg: &UdfCfg<Init>, _args: &ArgList<Init>) -> Result<Self, String> {
+        Ok(Self(Xxh64::new(0)))
+    }
+
+    fn process<'a>(
+        &'a mut self,
+        _cfg: &UdfCfg<Process>,
+        _args: &ArgList<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<Self::Returns<'a>, ProcessError> {
+        Ok(self.0.digest() as i64)
+    }
+}
+
+#[register(name = "xxhash64_agg", alias = "xxhash_agg")]
+impl AggregateUdf for XxHash64Agg {
+    fn clear(
+        &mut self,
+        _cfg: &UdfCfg<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<(), NonZeroU8> {
+        self.0.reset(0);
+        Ok(())
+    }
+
+    fn add(
+        &mut self,
+        _cfg: &UdfCfg<Process>,
+        args: &ArgList<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<(), NonZeroU8> {
+        args.iter()
+            .for_each(|arg| hash_arg(arg, |buf| self.0.update(buf)));
+        Ok(())
+    }
+}
+
+/// Validate arguments for HMAC functions, which take a key then any number of data
+/// arguments
+fn hmac_init(args: &ArgList<Init>, fn_name: &str) -> Result<(), String> {
+    let Some(mut key) = args.get(0) else {
+        return Err(format!(
+            "{fn_name} takes at least 1 argument but got 0 (usage: `{fn_name}(key, data...)`)"
+        ));
+    };
+    key.set_type_coercion(SqlType::String);
+    Ok(())
+}
+
+/// Compute the HMAC of all arguments after the first using the first as the key,
+/// writing the result to `out`. Returns `false` if the key is null.
+fn hmac_process<D>(args: &ArgList<Process>, out: &mut [u8]) -> bool
+where
+    D: Digest + BlockSizeUser,
+{
+    let key_arg = args.get(0).unwrap().value();
+    let Some(key) = key_arg.as_bytes() else {
+        return false;
+    };
+
+    // `SimpleHmac` accepts keys of any length so this cannot fail
+    let mut mac = <SimpleHmac<D> as KeyInit>::new_from_slice(key).unwrap();
+    args.iter()
+        .skip(1)
+        .for_each(|arg| hash_arg(arg, |buf| mac.update(buf)));
+    out.copy_from_slice(&mac.finalize().into_bytes());
+    true
+}
+
 /// Turn a SQL argument into a hashable buffer and pass it the given function
 fn hash_arg<T>(arg: SqlArg<Process>, mut hash_fn: impl FnMut(&[u8]) -> T) -> T {
     // Any non-null value will update the hash, null values do nothing.
@@ -376,3 +1264,167 @@ fn hash_arg<T>(arg: SqlArg<Process>, mut hash_fn: impl FnMut(&[u8]) -> T) -> T {
         _ => hash_fn([].as_slice()),
     }
 }
+
+/// Like `hash_arg`, but numbers are hashed as normalized decimal text so that equal
+/// values hash the same whatever their type. Decimals lose trailing fractional zeros
+/// and leading integer zeros, reals use their shortest round-trip representation,
+/// and zero is always `0` (never `-0`). Every NaN is hashed as `NaN`.
+///
+/// For example, the decimal `1.50`, the real `1.5`, and the string `'1.5'` all hash
+/// the same, as do the integer `2` and the decimal `2.00`.
+fn hash_arg_canonical<T>(arg: SqlArg<Process>, mut hash_fn: impl FnMut(&[u8]) -> T) -> T {
+    match arg.value() {
+        SqlResult::String(Some(buf)) => hash_fn(buf),
+        SqlResult::Real(Some(f)) => hash_fn(canonical_real(f).as_bytes()),
+        SqlResult::Int(Some(i)) => hash_fn(i.to_string().as_bytes()),
+        SqlResult::Decimal(Some(d)) => hash_fn(canonical_decimal(d).as_bytes()),
+        _ => hash_fn([].as_slice()),
+    }
+}
+
+/// Format a real without exponent notation, normalizing zero and NaN
+fn canonical_real(f: f64) -> String {
+    if f.is_nan() {
+        "NaN".to_owned()
+    } else if f == 0.0 {
+        "0".to_owned()
+    } else {
+        // `Display` for floats is the shortest string that round trips
+        f.to_string()
+    }
+}
+
+/// Strip the sign of zero, `+`, leading integer zeros, and trailing fractional zeros
+fn canonical_decimal(d: &str) -> String {
+    let d = d.trim();
+    let (negative, digits) = match d.as_bytes().first() {
+        Some(b'-') => (true, &d[1..]),
+        Some(b'+') => (false, &d[1..]),
+        _ => (false, d),
+    };
+    let (int, frac) = digits.split_once('.').unwrap_or((digits, ""));
+    let int = int.trim_start_matches('0');
+    let frac = frac.trim_end_matches('0');
+
+    let mut ret = String::with_capacity(d.len() + 1);
+    if negative && !(int.is_empty() && frac.is_empty()) {
+        ret.push('-');
+    }
+    ret.push_str(if int.is_empty() { "0" } else { int });
+    if !frac.is_empty() {
+        ret.push('.');
+        ret.push_str(frac);
+    }
+    ret
+}
+
+/// `SqlResult` of any type that is `NULL`
+fn is_null(value: &SqlResult) -> bool {
+    matches!(
+        value,
+        SqlResult::String(None)
+            | SqlResult::Real(None)
+            | SqlResult::Int(None)
+            | SqlResult::Decimal(None)
+    )
+}
+
+// Type tags used by `hash_arg_framed`
+const TAG_NULL: u8 = 0x00;
+const TAG_STRING: u8 = 0x01;
+const TAG_INT: u8 = 0x02;
+const TAG_REAL: u8 = 0x03;
+const TAG_DECIMAL: u8 = 0x04;
+
+/// Like `hash_arg`, but encode the argument so that a sequence of arguments is
+/// unambiguous. Each argument is written as a one byte type tag, then (for non-null
+/// values) the data length as a little endian `u64`, then the data itself. Data is
+/// the same as what `hash_arg` uses.
+fn hash_arg_framed(arg: SqlArg<Process>, mut hash_fn: impl FnMut(&[u8])) {
+    let mut write = |tag: u8, buf: &[u8]| {
+        hash_fn(&[tag]);
+        hash_fn(&(buf.len() as u64).to_le_bytes());
+        hash_fn(buf);
+    };
+
+    match arg.value() {
+        SqlResult::String(Some(buf)) => write(TAG_STRING, buf),
+        SqlResult::Real(Some(f)) => write(TAG_REAL, &f.to_le_bytes()),
+        SqlResult::Int(Some(i)) => write(TAG_INT, &i.to_le_bytes()),
+        SqlResult::Decimal(Some(d)) => write(TAG_DECIMAL, d.as_bytes()),
+        _ => hash_fn(&[TAG_NULL]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use udf::mock::*;
+
+    use super::*;
+
+    /// Run `sha256_tuple` with the given arguments
+    fn sha256_tuple_hex(mut args: MockArgList) -> String {
+        let mut cfg = MockUdfCfg::new();
+        let mut udf = sha256_tuple::init(cfg.as_init(), args.as_init()).unwrap();
+        let res = udf
+            .process(cfg.as_process(), args.as_process(), None)
+            .unwrap();
+        String::from_utf8(res.to_owned()).unwrap()
+    }
+
+    #[test]
+    fn test_tuple_framing() {
+        // Reference values created by framing manually
+        assert_eq!(
+            sha256_tuple_hex(mock_args![
+                ("Hello, ", "", false),
+                ("world", "", false),
+                ("!", "", false)
+            ]),
+            "1B961656C9A1213974CC79755CE74CBD4021F66126E675F54CF236DD89B275BD"
+        );
+        assert_eq!(
+            sha256_tuple_hex(mock_args![
+                ("Hello, world!", "", false),
+                (String None, "", true),
+                (Int 1, "", false)
+            ]),
+            "94884E88F466D1BD7CA168095E2CF1B16020C3370AB8239307F329C320193D70"
+        );
+
+        // Splitting arguments differently changes the result
+        assert_ne!(
+            sha256_tuple_hex(mock_args![("ab", "", false), ("c", "", false)]),
+            sha256_tuple_hex(mock_args![("a", "", false), ("bc", "", false)])
+        );
+
+        // Null is different from an empty string
+        assert_ne!(
+            sha256_tuple_hex(mock_args![("", "", false)]),
+            sha256_tuple_hex(mock_args![(String None, "", true)])
+        );
+
+        // Integers are different from strings with the same bytes
+        assert_ne!(
+            sha256_tuple_hex(mock_args![(Int 1, "", false)]),
+            sha256_tuple_hex(mock_args![("\x01\0\0\0\0\0\0\0", "", false)])
+        );
+    }
+
+    #[test]
+    fn test_canonical_numbers() {
+        assert_eq!(canonical_decimal("1.50"), "1.5");
+        assert_eq!(canonical_decimal("-001.500"), "-1.5");
+        assert_eq!(canonical_decimal("+2.000"), "2");
+        assert_eq!(canonical_decimal("-0.00"), "0");
+        assert_eq!(canonical_decimal(".25"), "0.25");
+        assert_eq!(canonical_decimal("100"), "100");
+
+        assert_eq!(canonical_real(1.5), "1.5");
+        assert_eq!(canonical_real(2.0), "2");
+        assert_eq!(canonical_real(-0.0), "0");
+        assert_eq!(canonical_real(-f64::NAN), "NaN");
+        assert_eq!(canonical_real(1e-7), "0.0000001");
+        assert_eq!(canonical_real(f64::NEG_INFINITY), "-inf");
+    }
+}
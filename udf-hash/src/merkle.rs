@@ -0,0 +1,331 @@
+//! Merkle tree roots with RFC 6962 (Certificate Transparency) domain separation
+//!
+//! Each row is a leaf, and its arguments are concatenated the same way the plain
+//! hash functions do it. Leaves are hashed as `H(0x00 || data)` and interior nodes
+//! as `H(0x01 || left || right)`. For `n` leaves, the left subtree holds the largest
+//! power of two less than `n`, and an empty tree has the root `H("")`.
+//!
+//! The root depends on the order that rows are added, which is the order the server
+//! reads them. Aggregate over a source with a fixed order, such as a derived table
+//! with `ORDER BY` and `LIMIT`.
+//!
+//! `merkle_root_agg` and `merkle_leaf_hash` use SHA-256 like Certificate
+//! Transparency, and `merkle_root_blake3_agg` and `merkle_leaf_blake3` use BLAKE3.
+
+use digest::Digest;
+use udf::prelude::*;
+
+use crate::hash_arg;
+
+/// Prefix for leaf hashes
+const LEAF_PREFIX: u8 = 0x00;
+/// Prefix for interior node hashes
+const NODE_PREFIX: u8 = 0x01;
+const HASH_LEN: usize = 32;
+
+/// A 256-bit hash that can be used for tree nodes
+trait TreeHasher: Default {
+    fn update(&mut self, buf: &[u8]);
+    fn finalize_reset(&mut self) -> [u8; HASH_LEN];
+}
+
+impl TreeHasher for sha2::Sha256 {
+    fn update(&mut self, buf: &[u8]) {
+        Digest::update(self, buf);
+    }
+
+    fn finalize_reset(&mut self) -> [u8; HASH_LEN] {
+        Digest::finalize_reset(self).into()
+    }
+}
+
+impl TreeHasher for blake3::Hasher {
+    fn update(&mut self, buf: &[u8]) {
+        blake3::Hasher::update(self, buf);
+    }
+
+    fn finalize_reset(&mut self) -> [u8; HASH_LEN] {
+        let ret = self.finalize().into();
+        self.reset();
+        ret
+    }
+}
+
+/// Hash the arguments of a row as a leaf
+fn leaf_hash<H: TreeHasher>(hasher: &mut H, args: &ArgList<Process>) -> [u8; HASH_LEN] {
+    hasher.update(&[LEAF_PREFIX]);
+    args.iter()
+        .for_each(|arg| hash_arg(arg, |buf| hasher.update(buf)));
+    hasher.finalize_reset()
+}
+
+fn node_hash<H: TreeHasher>(
+    hasher: &mut H,
+    left: &[u8; HASH_LEN],
+    right: &[u8; HASH_LEN],
+) -> [u8; HASH_LEN] {
+    hasher.update(&[NODE_PREFIX]);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize_reset()
+}
+
+/// Streaming tree builder that only keeps the roots of complete subtrees
+#[derive(Default)]
+struct MerkleTree<H> {
+    hasher: H,
+    /// Roots of perfect subtrees with their height, largest first. Heights are
+    /// strictly decreasing, like the set bits of the leaf count.
+    stack: Vec<(u32, [u8; HASH_LEN])>,
+}
+
+impl<H: TreeHasher> MerkleTree<H> {
+    fn clear(&mut self) {
+        self.stack.clear();
+    }
+
+    fn push_leaf(&mut self, leaf: [u8; HASH_LEN]) {
+        let mut entry = (0, leaf);
+        while let Some((height, left)) = self.stack.last() {
+            if *height != entry.0 {
+                break;
+            }
+            entry = (height + 1, node_hash(&mut self.hasher, left, &entry.1));
+            self.stack.pop();
+        }
+        self.stack.push(entry);
+    }
+
+    fn add_row(&mut self, args: &ArgList<Process>) {
+        let leaf = leaf_hash(&mut self.hasher, args);
+        self.push_leaf(leaf);
+    }
+
+    /// Combine the subtrees from right to left, which matches the RFC 6962 split
+    fn root(&mut self) -> [u8; HASH_LEN] {
+        let mut iter = self.stack.iter().rev();
+        let Some((_, last)) = iter.next() else {
+            return self.hasher.finalize_reset();
+        };
+        iter.fold(*last, |acc, (_, left)| {
+            node_hash(&mut self.hasher, left, &acc)
+        })
+    }
+}
+
+// Create the root aggregates `$name_agg` and `$name_agg_bin`, and the leaf hashes
+// `$leaf_name` and `$leaf_name_bin`
+macro_rules! merkle_udf {
+    ($hash_ty:ty, $name:ident, $leaf_name:ident) => {
+        paste::paste! {
+            #[allow(non_camel_case_types)]
+            struct [<$name _agg>] {
+                tree: MerkleTree<$hash_ty>,
+                hex: [u8; HASH_LEN * 2],
+            }
+
+            #[register]
+            impl BasicUdf for [<$name _agg>] {
+                type Returns<'a> = &'a [u8];
+
+                fn init(_cfg: &UdfCfg<Init>, _args: &ArgList<Init>) -> Result<Self, String> {
+                    Ok(Self {
+                        tree: MerkleTree::default(),
+                        hex: [0u8; HASH_LEN * 2],
+                    })
+                }
+
+                fn process<'a>(
+                    &'a mut self,
+                    _cfg: &UdfCfg<Process>,
+                    _args: &ArgList<Process>,
+                    _error: Option<NonZeroU8>,
+                ) -> Result<Self::Returns<'a>, ProcessError> {
+                    data_encoding::HEXUPPER.encode_mut(&self.tree.root(), &mut self.hex);
+                    Ok(&self.hex)
+                }
+            }
+
+            #[register]
+            impl AggregateUdf for [<$name _agg>] {
+                fn clear(
+                    &mut self,
+                    _cfg: &UdfCfg<Process>,
+                    _error: Option<NonZeroU8>,
+                ) -> Result<(), NonZeroU8> {
+                    self.tree.clear();
+                    Ok(())
+                }
+
+                fn add(
+                    &mut self,
+                    _cfg: &UdfCfg<Process>,
+                    args: &ArgList<Process>,
+                    _error: Option<NonZeroU8>,
+                ) -> Result<(), NonZeroU8> {
+                    self.tree.add_row(args);
+                    Ok(())
+                }
+            }
+
+            #[allow(non_camel_case_types)]
+            struct [<$name _agg_bin>] {
+                tree: MerkleTree<$hash_ty>,
+                ret: [u8; HASH_LEN],
+            }
+
+            #[register]
+            impl BasicUdf for [<$name _agg_bin>] {
+                type Returns<'a> = &'a [u8];
+
+                fn init(_cfg: &UdfCfg<Init>, _args: &ArgList<Init>) -> Result<Self, String> {
+                    Ok(Self {
+                        tree: MerkleTree::default(),
+                        ret: [0u8; HASH_LEN],
+                    })
+                }
+
+                fn process<'a>(
+                    &'a mut self,
+                    _cfg: &UdfCfg<Process>,
+                    _args: &ArgList<Process>,
+                    _error: Option<NonZeroU8>,
+                ) -> Result<Self::Returns<'a>, ProcessError> {
+                    self.ret = self.tree.root();
+                    Ok(&self.ret)
+                }
+            }
+
+            #[register]
+            impl AggregateUdf for [<$name _agg_bin>] {
+                fn clear(
+                    &mut self,
+                    _cfg: &UdfCfg<Process>,
+                    _error: Option<NonZeroU8>,
+                ) -> Result<(), NonZeroU8> {
+                    self.tree.clear();
+                    Ok(())
+                }
+
+                fn add(
+                    &mut self,
+                    _cfg: &UdfCfg<Process>,
+                    args: &ArgList<Process>,
+                    _error: Option<NonZeroU8>,
+                ) -> Result<(), NonZeroU8> {
+                    self.tree.add_row(args);
+                    Ok(())
+                }
+            }
+
+            #[allow(non_camel_case_types)]
+            struct $leaf_name {
+                hasher: $hash_ty,
+                hex: [u8; HASH_LEN * 2],
+            }
+
+            #[register]
+            impl BasicUdf for $leaf_name {
+                type Returns<'a> = &'a [u8];
+
+                fn init(_cfg: &UdfCfg<Init>, _args: &ArgList<Init>) -> Result<Self, String> {
+                    Ok(Self {
+                        hasher: <$hash_ty>::default(),
+                        hex: [0u8; HASH_LEN * 2],
+                    })
+                }
+
+                fn process<'a>(
+                    &'a mut self,
+                    _cfg: &UdfCfg<Process>,
+                    args: &ArgList<Process>,
+                    _error: Option<NonZeroU8>,
+                ) -> Result<Self::Returns<'a>, ProcessError> {
+                    let leaf = leaf_hash(&mut self.hasher, args);
+                    data_encoding::HEXUPPER.encode_mut(&leaf, &mut self.hex);
+                    Ok(&self.hex)
+                }
+            }
+
+            #[allow(non_camel_case_types)]
+            struct [<$leaf_name _bin>] {
+                hasher: $hash_ty,
+                ret: [u8; HASH_LEN],
+            }
+
+            #[register]
+            impl BasicUdf for [<$leaf_name _bin>] {
+                type Returns<'a> = &'a [u8];
+
+                fn init(_cfg: &UdfCfg<Init>, _args: &ArgList<Init>) -> Result<Self, String> {
+                    Ok(Self {
+                        hasher: <$hash_ty>::default(),
+                        ret: [0u8; HASH_LEN],
+                    })
+                }
+
+                fn process<'a>(
+                    &'a mut self,
+                    _cfg: &UdfCfg<Process>,
+                    args: &ArgList<Process>,
+                    _error: Option<NonZeroU8>,
+                ) -> Result<Self::Returns<'a>, ProcessError> {
+                    self.ret = leaf_hash(&mut self.hasher, args);
+                    Ok(&self.ret)
+                }
+            }
+        }
+    };
+}
+
+merkle_udf!(sha2::Sha256, merkle_root, merkle_leaf_hash);
+merkle_udf!(blake3::Hasher, merkle_root_blake3, merkle_leaf_blake3);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sha256_root(leaves: &[&[u8]]) -> String {
+        let mut tree = MerkleTree::<sha2::Sha256>::default();
+        for leaf in leaves {
+            let mut hasher = sha2::Sha256::default();
+            TreeHasher::update(&mut hasher, &[LEAF_PREFIX]);
+            TreeHasher::update(&mut hasher, leaf);
+            tree.push_leaf(TreeHasher::finalize_reset(&mut hasher));
+        }
+        data_encoding::HEXLOWER.encode(&tree.root())
+    }
+
+    #[test]
+    fn test_rfc6962_roots() {
+        // Test vectors from the Certificate Transparency reference implementation
+        let leaves: [&[u8]; 8] = [
+            b"",
+            b"\x00",
+            b"\x10",
+            b"\x20\x21",
+            b"\x30\x31",
+            b"\x40\x41\x42\x43",
+            b"\x50\x51\x52\x53\x54\x55\x56\x57",
+            b"\x60\x61\x62\x63\x64\x65\x66\x67\x68\x69\x6a\x6b\x6c\x6d\x6e\x6f",
+        ];
+        let roots = [
+            "6e340b9cffb37a989ca544e6bb780a2c78901d3fb33738768511a30617afa01d",
+            "fac54203e7cc696cf0dfcb42c92a1d9dbaf70ad9e621f4bd8d98662f00e3c125",
+            "aeb6bcfe274b70a14fb067a5e5578264db0fa9b51af5e0ba159158f329e06e77",
+            "d37ee418976dd95753c1c73862b9398fa2a2cf9b4ff0fdfe8b30cd95209614b7",
+            "4e3bbb1f7b478dcfe71fb631631519a3bca12c9aefca1612bfce4c13a86264d4",
+            "76e67dadbcdf1e10e1b74ddc608abd2f98dfb16fbce75277b5232a127f2087ef",
+            "ddb89be403809e325750d3d263cd78929c2942b7942a34b77e122c9594a74c8c",
+            "5dc9da79a70659a9ad559cb701ded9a2ab9d823aad2f4960cfe370eff4604328",
+        ];
+
+        assert_eq!(
+            sha256_root(&[]),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        for (n, root) in roots.iter().enumerate() {
+            assert_eq!(sha256_root(&leaves[..=n]), *root, "{} leaves", n + 1);
+        }
+    }
+}
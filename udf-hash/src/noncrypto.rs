@@ -0,0 +1,416 @@
+//! Fast non-cryptographic hashes and checksums
+//!
+//! Like the xxhash functions, these return integers. 32-bit results are always
+//! positive, 64-bit results are cast to a signed integer. `murmur3_128` returns
+//! hex (or binary with `murmur3_128_bin`) since it does not fit in an integer.
+//!
+//! `siphash24` and `highwayhash64` are keyed: the first argument is the key (16 and
+//! 32 bytes respectively) and the rest are the data.
+
+use std::hash::Hasher;
+
+use crc::{Crc, Digest, CRC_32_ISCSI, CRC_64_NVME};
+use highway::{HighwayHash, HighwayHasher, Key};
+use siphasher::sip::SipHasher24;
+use udf::prelude::*;
+
+use crate::hash_arg;
+
+static CRC32C: Crc<u32> = Crc::<u32>::new(&CRC_32_ISCSI);
+static CRC64_NVME: Crc<u64> = Crc::<u64>::new(&CRC_64_NVME);
+
+const FNV32_OFFSET: u32 = 0x811c_9dc5;
+const FNV32_PRIME: u32 = 0x0100_0193;
+const FNV64_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV64_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+const SIPHASH_KEY_LEN: usize = 16;
+const HIGHWAY_KEY_LEN: usize = 32;
+
+/// A streaming hasher with an integer result
+trait IntHasher {
+    fn update(&mut self, buf: &[u8]);
+    fn finish(self) -> i64;
+}
+
+impl IntHasher for Digest<'_, u32> {
+    fn update(&mut self, buf: &[u8]) {
+        Digest::<u32>::update(self, buf);
+    }
+
+    fn finish(self) -> i64 {
+        self.finalize().into()
+    }
+}
+
+impl IntHasher for Digest<'_, u64> {
+    fn update(&mut self, buf: &[u8]) {
+        Digest::<u64>::update(self, buf);
+    }
+
+    fn finish(self) -> i64 {
+        self.finalize() as i64
+    }
+}
+
+struct Fnv1a32(u32);
+
+impl IntHasher for Fnv1a32 {
+    fn update(&mut self, buf: &[u8]) {
+        for b in buf {
+            self.0 = (self.0 ^ u32::from(*b)).wrapping_mul(FNV32_PRIME);
+        }
+    }
+
+    fn finish(self) -> i64 {
+        self.0.into()
+    }
+}
+
+struct Fnv1a64(u64);
+
+impl IntHasher for Fnv1a64 {
+    fn update(&mut self, buf: &[u8]) {
+        for b in buf {
+            self.0 = (self.0 ^ u64::from(*b)).wrapping_mul(FNV64_PRIME);
+        }
+    }
+
+    fn finish(self) -> i64 {
+        self.0 as i64
+    }
+}
+
+impl IntHasher for SipHasher24 {
+    fn update(&mut self, buf: &[u8]) {
+        self.write(buf);
+    }
+
+    fn finish(self) -> i64 {
+        Hasher::finish(&self) as i64
+    }
+}
+
+impl IntHasher for HighwayHasher {
+    fn update(&mut self, buf: &[u8]) {
+        self.append(buf);
+    }
+
+    fn finish(self) -> i64 {
+        self.finalize64() as i64
+    }
+}
+
+/// Feed all arguments after `skip` to the hasher
+fn hash_args_int<H: IntHasher>(mut hasher: H, args: &ArgList<Process>, skip: usize) -> i64 {
+    args.iter()
+        .skip(skip)
+        .for_each(|arg| hash_arg(arg, |buf| hasher.update(buf)));
+    hasher.finish()
+}
+
+// Create an integer-returning UDF from an expression that creates an `IntHasher`
+macro_rules! int_hash_udf {
+    ($ty:ident, $name:literal, $hasher:expr) => {
+        struct $ty;
+
+        #[register(name = $name)]
+        impl BasicUdf for $ty {
+            type Returns<'a> = i64;
+
+            fn init(_cfg: &UdfCfg<Init>, _args: &ArgList<Init>) -> Result<Self, String> {
+                Ok(Self)
+            }
+
+            fn process<'a>(
+                &'a mut self,
+                _cfg: &UdfCfg<Process>,
+                args: &ArgList<Process>,
+                _error: Option<NonZeroU8>,
+            ) -> Result<Self::Returns<'a>, ProcessError> {
+                Ok(hash_args_int($hasher, args, 0))
+            }
+        }
+    };
+}
+
+int_hash_udf!(Crc32c, "crc32c", CRC32C.digest());
+int_hash_udf!(Crc64Nvme, "crc64_nvme", CRC64_NVME.digest());
+int_hash_udf!(Fnv1a32Udf, "fnv1a_32", Fnv1a32(FNV32_OFFSET));
+int_hash_udf!(Fnv1a64Udf, "fnv1a_64", Fnv1a64(FNV64_OFFSET));
+
+/// Collect all arguments into `buf`, for hashers that can't stream
+fn collect_args(buf: &mut Vec<u8>, args: &ArgList<Process>) {
+    buf.clear();
+    args.iter()
+        .for_each(|arg| hash_arg(arg, |b| buf.extend_from_slice(b)));
+}
+
+/// 32-bit murmur3 (x86 variant) with seed 0
+struct Murmur3_32 {
+    buf: Vec<u8>,
+}
+
+#[register(name = "murmur3_32")]
+impl BasicUdf for Murmur3_32 {
+    type Returns<'a> = i64;
+
+    fn init(_cfg: &UdfCfg<Init>, _args: &ArgList<Init>) -> Result<Self, String> {
+        Ok(Self { buf: Vec::new() })
+    }
+
+    fn process<'a>(
+        &'a mut self,
+        _cfg: &UdfCfg<Process>,
+        args: &ArgList<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<Self::Returns<'a>, ProcessError> {
+        collect_args(&mut self.buf, args);
+        // Reading from a slice can't fail
+        let hash = murmur3::murmur3_32(&mut self.buf.as_slice(), 0).unwrap();
+        Ok(hash.into())
+    }
+}
+
+/// 128-bit murmur3 (x64 variant) with seed 0, as the usual 16 byte output
+fn murmur3_128_bytes(buf: &[u8]) -> [u8; 16] {
+    let mut reader = buf;
+    // The two 64-bit halves are both little endian, which matches `to_le_bytes`
+    murmur3::murmur3_x64_128(&mut reader, 0)
+        .unwrap()
+        .to_le_bytes()
+}
+
+/// 128-bit murmur3 as hex
+struct Murmur3_128 {
+    buf: Vec<u8>,
+    hex: [u8; 32],
+}
+
+#[register(name = "murmur3_128")]
+impl BasicUdf for Murmur3_128 {
+    type Returns<'a> = &'a [u8];
+
+    fn init(_cfg: &UdfCfg<Init>, _args: &ArgList<Init>) -> Result<Self, String> {
+        Ok(Self {
+            buf: Vec::new(),
+            hex: [0u8; 32],
+        })
+    }
+
+    fn process<'a>(
+        &'a mut self,
+        _cfg: &UdfCfg<Process>,
+        args: &ArgList<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<Self::Returns<'a>, ProcessError> {
+        collect_args(&mut self.buf, args);
+        let hashed = murmur3_128_bytes(&self.buf);
+        data_encoding::HEXUPPER.encode_mut(&hashed, &mut self.hex);
+        Ok(&self.hex)
+    }
+}
+
+/// 128-bit murmur3 as binary
+struct Murmur3_128Bin {
+    buf: Vec<u8>,
+    ret: [u8; 16],
+}
+
+#[register(name = "murmur3_128_bin")]
+impl BasicUdf for Murmur3_128Bin {
+    type Returns<'a> = &'a [u8];
+
+    fn init(_cfg: &UdfCfg<Init>, _args: &ArgList<Init>) -> Result<Self, String> {
+        Ok(Self {
+            buf: Vec::new(),
+            ret: [0u8; 16],
+        })
+    }
+
+    fn process<'a>(
+        &'a mut self,
+        _cfg: &UdfCfg<Process>,
+        args: &ArgList<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<Self::Returns<'a>, ProcessError> {
+        collect_args(&mut self.buf, args);
+        self.ret = murmur3_128_bytes(&self.buf);
+        Ok(&self.ret)
+    }
+}
+
+/// Validate a constant key argument of a specific length
+fn key_init(args: &ArgList<Init>, fn_name: &str, key_len: usize) -> Result<(), String> {
+    let Some(mut key) = args.get(0) else {
+        return Err(format!(
+            "{fn_name} takes at least 1 argument but got 0 (usage: `{fn_name}(key, data...)`)"
+        ));
+    };
+
+    // Values must be checked before setting coercion
+    if let Some(k) = key.value().as_bytes() {
+        if k.len() != key_len {
+            return Err(format!(
+                "{fn_name} requires a key of {key_len} bytes but got {}",
+                k.len()
+            ));
+        }
+    }
+    key.set_type_coercion(SqlType::String);
+    Ok(())
+}
+
+/// SipHash-2-4 with a 16 byte key
+struct SipHash24;
+
+#[register(name = "siphash24")]
+impl BasicUdf for SipHash24 {
+    type Returns<'a> = Option<i64>;
+
+    fn init(_cfg: &UdfCfg<Init>, args: &ArgList<Init>) -> Result<Self, String> {
+        key_init(args, "siphash24", SIPHASH_KEY_LEN).map(|_| Self)
+    }
+
+    fn process<'a>(
+        &'a mut self,
+        _cfg: &UdfCfg<Process>,
+        args: &ArgList<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<Self::Returns<'a>, ProcessError> {
+        let key = args.get(0).unwrap().value();
+        let Some(key) = key.as_bytes().and_then(|k| <&[u8; 16]>::try_from(k).ok()) else {
+            return Ok(None);
+        };
+        Ok(Some(hash_args_int(SipHasher24::new_with_key(key), args, 1)))
+    }
+}
+
+/// HighwayHash with a 32 byte key, read as four little endian `u64`s
+struct HighwayHash64;
+
+#[register(name = "highwayhash64")]
+impl BasicUdf for HighwayHash64 {
+    type Returns<'a> = Option<i64>;
+
+    fn init(_cfg: &UdfCfg<Init>, args: &ArgList<Init>) -> Result<Self, String> {
+        key_init(args, "highwayhash64", HIGHWAY_KEY_LEN).map(|_| Self)
+    }
+
+    fn process<'a>(
+        &'a mut self,
+        _cfg: &UdfCfg<Process>,
+        args: &ArgList<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<Self::Returns<'a>, ProcessError> {
+        let key = args.get(0).unwrap().value();
+        let Some(key) = key.as_bytes().filter(|k| k.len() == HIGHWAY_KEY_LEN) else {
+            return Ok(None);
+        };
+
+        let mut words = [0u64; 4];
+        for (word, chunk) in words.iter_mut().zip(key.chunks_exact(8)) {
+            *word = u64::from_le_bytes(chunk.try_into().unwrap());
+        }
+        Ok(Some(hash_args_int(HighwayHasher::new(Key(words)), args, 1)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use udf::mock::*;
+
+    use super::*;
+
+    /// Run an integer-returning UDF and unwrap the result
+    fn run_int<U>(mut args: MockArgList) -> i64
+    where
+        U: for<'a> BasicUdf<Returns<'a> = i64>,
+    {
+        let mut cfg = MockUdfCfg::new();
+        let mut udf = U::init(cfg.as_init(), args.as_init()).unwrap();
+        udf.process(cfg.as_process(), args.as_process(), None)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_checksums() {
+        // Standard check values are for "123456789"
+        let args = || mock_args![("1234", "", false), ("56789", "", false)];
+        assert_eq!(run_int::<Crc32c>(args()), 0xe306_9283);
+        assert_eq!(
+            run_int::<Crc64Nvme>(args()),
+            0xae8b_1486_0a79_9888_u64 as i64
+        );
+
+        let args = || mock_args![("foo", "", false), ("bar", "", false)];
+        assert_eq!(run_int::<Fnv1a32Udf>(args()), 0xbf9c_f968);
+        assert_eq!(
+            run_int::<Fnv1a64Udf>(args()),
+            0x8594_4171_f739_67e8_u64 as i64
+        );
+    }
+
+    #[test]
+    fn test_murmur3() {
+        let text = "The quick brown fox jumps over the lazy dog";
+        assert_eq!(
+            run_int::<Murmur3_32>(mock_args![(text, "", false)]),
+            0x2e4f_f723
+        );
+
+        assert_eq!(
+            run_int::<Murmur3_32>(mock_args![("hel", "", false), ("lo", "", false)]),
+            0x248b_fa47
+        );
+
+        // Vectors from Guava's `Hashing.murmur3_128()`, where the digest is `h1` then
+        // `h2`, each little endian
+        for (text, expected) in [
+            ("", "00000000000000000000000000000000"),
+            ("hello", "029BBD41B3A7D8CB191DAE486A901E5B"),
+            ("hell", "67F8103E694299624753EBBA820BDB92"),
+            (text, "6C1B07BC7BBC4BE347939AC4A93C437A"),
+        ] {
+            let mut cfg = MockUdfCfg::new();
+            let mut args = mock_args![(text, "", false)];
+            let mut udf = Murmur3_128::init(cfg.as_init(), args.as_init()).unwrap();
+            let res = udf
+                .process(cfg.as_process(), args.as_process(), None)
+                .unwrap();
+            assert_eq!(res, expected.as_bytes(), "{text}");
+
+            let mut udf = Murmur3_128Bin::init(cfg.as_init(), args.as_init()).unwrap();
+            let res = udf
+                .process(cfg.as_process(), args.as_process(), None)
+                .unwrap();
+            assert_eq!(data_encoding::HEXUPPER.encode(res), expected, "{text}");
+        }
+    }
+
+    #[test]
+    fn test_keyed() {
+        let key: Vec<u8> = (0..32).collect();
+        let key16 = std::str::from_utf8(&key[..16]).unwrap();
+        let key32 = std::str::from_utf8(&key).unwrap();
+        let mut cfg = MockUdfCfg::new();
+
+        // Reference vectors with an empty message
+        let mut args = mock_args![(key16, "", false)];
+        let mut udf = SipHash24::init(cfg.as_init(), args.as_init()).unwrap();
+        let res = udf
+            .process(cfg.as_process(), args.as_process(), None)
+            .unwrap();
+        assert_eq!(res, Some(0x726f_db47_dd0e_0e31));
+
+        let mut args = mock_args![(key32, "", false)];
+        let mut udf = HighwayHash64::init(cfg.as_init(), args.as_init()).unwrap();
+        let res = udf
+            .process(cfg.as_process(), args.as_process(), None)
+            .unwrap();
+        assert_eq!(res, Some(0x907a_56de_22c2_6e53_u64 as i64));
+
+        let mut args = mock_args![(key32, "", false), ("data", "", false)];
+        assert!(SipHash24::init(cfg.as_init(), args.as_init()).is_err());
+    }
+}
@@ -0,0 +1,731 @@
+//! Password hashing and verification
+//!
+//! Hashes are produced as PHC strings (`$argon2id$v=19$m=...`), or the modular
+//! crypt format for bcrypt (`$2b$12$...`), with a random salt unless one is given.
+//! `password_verify` detects the scheme from the stored hash and compares in
+//! constant time.
+//!
+//! bcrypt ignores everything after the first 72 bytes of a password, so
+//! `bcrypt_hash` returns `NULL` for longer passwords rather than silently truncating
+//! them, and `password_verify` never matches them against a bcrypt hash.
+//!
+//! These functions are deliberately slow, so avoid running them over large tables
+//! without a `WHERE` clause.
+
+use std::fmt::Write;
+
+use argon2::Argon2;
+use password_hash::rand_core::OsRng;
+use password_hash::{Ident, PasswordHash, PasswordHasher, PasswordVerifier, Salt, SaltString};
+use pbkdf2::Pbkdf2;
+use scrypt::Scrypt;
+use udf::prelude::*;
+
+/// Algorithm identifier for PBKDF2 with HMAC-SHA256
+const PBKDF2_SHA256: &str = "pbkdf2-sha256";
+/// Allowed PBKDF2 output lengths
+const PBKDF2_LEN_RANGE: std::ops::RangeInclusive<i64> = 10..=64;
+/// Allowed raw PBKDF2 salt lengths, such that the base64 salt fits in a PHC string
+const PBKDF2_SALT_RANGE: std::ops::RangeInclusive<usize> = 4..=48;
+/// Most bcrypt cost (log2 of the rounds) we will run
+const BCRYPT_MAX_COST: i64 = 16;
+/// bcrypt only uses this many bytes of the password and ignores the rest
+const BCRYPT_MAX_PASSWORD_LEN: usize = 72;
+/// Allowed bcrypt costs
+const BCRYPT_COST_RANGE: std::ops::RangeInclusive<i64> = 4..=BCRYPT_MAX_COST;
+/// Most PBKDF2 iterations we will run
+const PBKDF2_MAX_ITERATIONS: i64 = 10_000_000;
+/// Most argon2 memory in KiB (1 GiB)
+const ARGON2_MAX_M_COST: u32 = 1024 * 1024;
+/// Most argon2 iterations
+const ARGON2_MAX_T_COST: u32 = 16;
+/// Most argon2 lanes
+const ARGON2_MAX_P_COST: u32 = 16;
+/// Most scrypt log2 cost
+const SCRYPT_MAX_LOG_N: u32 = 20;
+/// Most scrypt memory in bytes (`128 * r * 2^ln`, 1 GiB)
+const SCRYPT_MAX_MEM: u64 = 1 << 30;
+/// Most scrypt parallelism
+const SCRYPT_MAX_P: u32 = 16;
+
+/// Parse a PHC-style parameter string such as `m=19456,t=2,p=1`. Every parameter is
+/// optional; missing ones keep the value from `defaults`.
+fn parse_params<const N: usize>(
+    fn_name: &str,
+    params: &[u8],
+    names: [&str; N],
+    mut defaults: [u32; N],
+) -> Result<[u32; N], String> {
+    let params = std::str::from_utf8(params)
+        .map_err(|_| format!("{fn_name}: parameters must be valid UTF-8"))?;
+
+    for param in params.split(',').filter(|p| !p.is_empty()) {
+        let (name, value) = param
+            .split_once('=')
+            .ok_or_else(|| format!("{fn_name}: expected `name=value` but got '{param}'"))?;
+        let idx = names.iter().position(|n| *n == name).ok_or_else(|| {
+            format!(
+                "{fn_name}: unknown parameter '{name}'; expected one of {}",
+                names.join(", ")
+            )
+        })?;
+        defaults[idx] = value
+            .parse()
+            .map_err(|_| format!("{fn_name}: parameter '{name}' must be an integer"))?;
+    }
+
+    Ok(defaults)
+}
+
+/// Error if a cost parameter is above its limit
+fn check_max<T: PartialOrd + std::fmt::Display>(
+    fn_name: &str,
+    name: &str,
+    value: T,
+    max: T,
+) -> Result<(), String> {
+    if value > max {
+        return Err(format!(
+            "{fn_name}: parameter '{name}' may be at most {max}, got {value}"
+        ));
+    }
+    Ok(())
+}
+
+/// Check that argon2 costs are within the limits, so a query can't exhaust the
+/// server's memory or CPU
+fn argon2_limits(fn_name: &str, m: u32, t: u32, p: u32) -> Result<(), String> {
+    check_max(fn_name, "m", m, ARGON2_MAX_M_COST)?;
+    check_max(fn_name, "t", t, ARGON2_MAX_T_COST)?;
+    check_max(fn_name, "p", p, ARGON2_MAX_P_COST)
+}
+
+/// Check that scrypt costs are within the limits
+fn scrypt_limits(fn_name: &str, ln: u32, r: u32, p: u32) -> Result<(), String> {
+    check_max(fn_name, "ln", ln, SCRYPT_MAX_LOG_N)?;
+    check_max(fn_name, "p", p, SCRYPT_MAX_P)?;
+    let mem = (128 * u64::from(r)) << ln;
+    if mem > SCRYPT_MAX_MEM {
+        return Err(format!(
+            "{fn_name}: parameters need {mem} bytes of memory (128 * r * 2^ln), \
+            but at most {SCRYPT_MAX_MEM} is allowed"
+        ));
+    }
+    Ok(())
+}
+
+/// Parse argon2 parameters `m` (memory in KiB), `t` (iterations), and `p` (lanes)
+fn argon2_params(params: &[u8]) -> Result<argon2::Params, String> {
+    let [m, t, p] = parse_params(
+        "argon2id_hash",
+        params,
+        ["m", "t", "p"],
+        [
+            argon2::Params::DEFAULT_M_COST,
+            argon2::Params::DEFAULT_T_COST,
+            argon2::Params::DEFAULT_P_COST,
+        ],
+    )?;
+    argon2_limits("argon2id_hash", m, t, p)?;
+    argon2::Params::new(m, t, p, None).map_err(|e| format!("argon2id_hash: {e}"))
+}
+
+/// Parse scrypt parameters `ln` (log2 of the cost), `r` (block size), and `p`
+/// (parallelism)
+fn scrypt_params(params: &[u8]) -> Result<scrypt::Params, String> {
+    let [ln, r, p] = parse_params(
+        "scrypt_hash",
+        params,
+        ["ln", "r", "p"],
+        [
+            scrypt::Params::RECOMMENDED_LOG_N.into(),
+            scrypt::Params::RECOMMENDED_R,
+            scrypt::Params::RECOMMENDED_P,
+        ],
+    )?;
+    scrypt_limits("scrypt_hash", ln, r, p)?;
+    let ln = u8::try_from(ln).map_err(|_| format!("scrypt_hash: invalid ln {ln}"))?;
+    scrypt::Params::new(ln, r, p, scrypt::Params::RECOMMENDED_LEN)
+        .map_err(|e| format!("scrypt_hash: {e}"))
+}
+
+/// Parameters that are parsed in `init` if constant, or per row otherwise
+#[derive(Debug)]
+enum MaybeConst<T> {
+    /// No parameter argument was given
+    Default,
+    Const(T),
+    PerRow,
+}
+
+impl<T: Clone> MaybeConst<T> {
+    /// Check an optional parameter argument, parsing it if it is constant
+    fn init(
+        args: &ArgList<Init>,
+        idx: usize,
+        parse: impl FnOnce(&[u8]) -> Result<T, String>,
+    ) -> Result<Self, String> {
+        let Some(mut arg) = args.get(idx) else {
+            return Ok(Self::Default);
+        };
+        let ret = match arg.value().as_bytes() {
+            Some(params) => Self::Const(parse(params)?),
+            None => Self::PerRow,
+        };
+        arg.set_type_coercion(SqlType::String);
+        Ok(ret)
+    }
+
+    /// Get the parameters for this row, `None` if they are invalid
+    fn get(
+        &self,
+        args: &ArgList<Process>,
+        idx: usize,
+        default: impl FnOnce() -> T,
+        parse: impl FnOnce(&[u8]) -> Result<T, String>,
+    ) -> Option<T> {
+        match self {
+            Self::Default => Some(default()),
+            Self::Const(v) => Some(v.clone()),
+            Self::PerRow => parse(args.get(idx).unwrap().value().as_bytes()?).ok(),
+        }
+    }
+}
+
+/// Check that the first argument exists and set it to be a string
+fn password_init(
+    args: &ArgList<Init>,
+    fn_name: &str,
+    usage: &str,
+    max_args: usize,
+) -> Result<(), String> {
+    if args.is_empty() || args.len() > max_args {
+        return Err(format!(
+            "{fn_name} takes 1 to {max_args} arguments but got {} (usage: `{fn_name}({usage})`)",
+            args.len()
+        ));
+    }
+    args.get(0).unwrap().set_type_coercion(SqlType::String);
+    Ok(())
+}
+
+/// Hash a password with a PHC hasher and a random salt, writing the result to `ret`
+fn phc_hash(
+    hasher: &impl PasswordHasher,
+    password: &[u8],
+    ret: &mut String,
+) -> Result<(), ProcessError> {
+    let salt = SaltString::generate(&mut OsRng);
+    let hash = hasher
+        .hash_password(password, &salt)
+        .map_err(|_| ProcessError)?;
+    ret.clear();
+    write!(ret, "{hash}").unwrap();
+    Ok(())
+}
+
+/// Argon2id with an optional parameter string, e.g. `m=19456,t=2,p=1`
+#[derive(Debug)]
+struct Argon2idHash {
+    params: MaybeConst<argon2::Params>,
+    ret: String,
+}
+
+#[register(name = "argon2id_hash")]
+impl BasicUdf for Argon2idHash {
+    type Returns<'a> = Option<&'a str>;
+
+    fn init(_cfg: &UdfCfg<Init>, args: &ArgList<Init>) -> Result<Self, String> {
+        password_init(args, "argon2id_hash", "password[, params]", 2)?;
+        Ok(Self {
+            params: MaybeConst::init(args, 1, argon2_params)?,
+            ret: String::new(),
+        })
+    }
+
+    fn process<'a>(
+        &'a mut self,
+        _cfg: &UdfCfg<Process>,
+        args: &ArgList<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<Self::Returns<'a>, ProcessError> {
+        let password = args.get(0).unwrap().value();
+        let Some(password) = password.as_bytes() else {
+            return Ok(None);
+        };
+        let Some(params) = self
+            .params
+            .get(args, 1, argon2::Params::default, argon2_params)
+        else {
+            return Ok(None);
+        };
+
+        let hasher = Argon2::new(argon2::Algorithm::Argon2id, argon2::Version::V0x13, params);
+        phc_hash(&hasher, password, &mut self.ret)?;
+        Ok(Some(&self.ret))
+    }
+}
+
+/// Scrypt with an optional parameter string, e.g. `ln=17,r=8,p=1`
+#[derive(Debug)]
+struct ScryptHash {
+    params: MaybeConst<scrypt::Params>,
+    ret: String,
+}
+
+#[register(name = "scrypt_hash")]
+impl BasicUdf for ScryptHash {
+    type Returns<'a> = Option<&'a str>;
+
+    fn init(_cfg: &UdfCfg<Init>, args: &ArgList<Init>) -> Result<Self, String> {
+        password_init(args, "scrypt_hash", "password[, params]", 2)?;
+        Ok(Self {
+            params: MaybeConst::init(args, 1, scrypt_params)?,
+            ret: String::new(),
+        })
+    }
+
+    fn process<'a>(
+        &'a mut self,
+        _cfg: &UdfCfg<Process>,
+        args: &ArgList<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<Self::Returns<'a>, ProcessError> {
+        let password = args.get(0).unwrap().value();
+        let Some(password) = password.as_bytes() else {
+            return Ok(None);
+        };
+        let Some(params) = self
+            .params
+            .get(args, 1, scrypt::Params::default, scrypt_params)
+        else {
+            return Ok(None);
+        };
+
+        let salt = SaltString::generate(&mut OsRng);
+        let hash = Scrypt
+            .hash_password_customized(password, None, None, params, &salt)
+            .map_err(|_| ProcessError)?;
+        self.ret.clear();
+        write!(self.ret, "{hash}").unwrap();
+        Ok(Some(&self.ret))
+    }
+}
+
+/// Bcrypt with the given cost, or the default cost of 12
+#[derive(Debug)]
+struct BcryptHash {
+    ret: String,
+}
+
+#[register(name = "bcrypt_hash")]
+impl BasicUdf for BcryptHash {
+    type Returns<'a> = Option<&'a str>;
+
+    fn init(_cfg: &UdfCfg<Init>, args: &ArgList<Init>) -> Result<Self, String> {
+        password_init(args, "bcrypt_hash", "password[, cost]", 2)?;
+        if let Some(mut cost) = args.get(1) {
+            if let Some(c) = cost.value().as_int() {
+                if !BCRYPT_COST_RANGE.contains(&c) {
+                    return Err(format!(
+                        "bcrypt_hash: cost must be between {} and {}, got {c}",
+                        BCRYPT_COST_RANGE.start(),
+                        BCRYPT_COST_RANGE.end()
+                    ));
+                }
+            }
+            cost.set_type_coercion(SqlType::Int);
+        }
+
+        Ok(Self { ret: String::new() })
+    }
+
+    fn process<'a>(
+        &'a mut self,
+        _cfg: &UdfCfg<Process>,
+        args: &ArgList<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<Self::Returns<'a>, ProcessError> {
+        let password = args.get(0).unwrap().value();
+        let Some(password) = password.as_bytes() else {
+            return Ok(None);
+        };
+        let cost = match args.get(1) {
+            Some(cost) => match cost.value().as_int() {
+                Some(c) if BCRYPT_COST_RANGE.contains(&c) => c as u32,
+                _ => return Ok(None),
+            },
+            None => bcrypt::DEFAULT_COST,
+        };
+
+        if password.len() > BCRYPT_MAX_PASSWORD_LEN {
+            udf_log!(
+                Warning: "bcrypt_hash: passwords may be at most {BCRYPT_MAX_PASSWORD_LEN} bytes, got {}",
+                password.len()
+            );
+            return Ok(None);
+        }
+
+        self.ret = bcrypt::hash(password, cost).map_err(|_| ProcessError)?;
+        Ok(Some(&self.ret))
+    }
+}
+
+/// PBKDF2-HMAC-SHA256 with a caller-provided salt
+#[derive(Debug)]
+struct Pbkdf2Sha256 {
+    ret: String,
+}
+
+#[register(name = "pbkdf2_sha256")]
+impl BasicUdf for Pbkdf2Sha256 {
+    type Returns<'a> = Option<&'a str>;
+
+    fn init(_cfg: &UdfCfg<Init>, args: &ArgList<Init>) -> Result<Self, String> {
+        if args.len() != 4 {
+            return Err(format!(
+                "pbkdf2_sha256 takes 4 arguments but got {} \
+                (usage: `pbkdf2_sha256(password, salt, iterations, len)`)",
+                args.len()
+            ));
+        }
+
+        // Values must be checked before setting coercion
+        if let Some(salt) = args.get(1).unwrap().value().as_bytes() {
+            if !PBKDF2_SALT_RANGE.contains(&salt.len()) {
+                return Err(format!(
+                    "pbkdf2_sha256: salt must be between {} and {} bytes, got {}",
+                    PBKDF2_SALT_RANGE.start(),
+                    PBKDF2_SALT_RANGE.end(),
+                    salt.len()
+                ));
+            }
+        }
+        if let Some(iterations) = args.get(2).unwrap().value().as_int() {
+            if !(1..=PBKDF2_MAX_ITERATIONS).contains(&iterations) {
+                return Err(format!(
+                    "pbkdf2_sha256: iterations must be between 1 and \
+                    {PBKDF2_MAX_ITERATIONS}, got {iterations}"
+                ));
+            }
+        }
+        if let Some(len) = args.get(3).unwrap().value().as_int() {
+            if !PBKDF2_LEN_RANGE.contains(&len) {
+                return Err(format!(
+                    "pbkdf2_sha256: length must be between {} and {}, got {len}",
+                    PBKDF2_LEN_RANGE.start(),
+                    PBKDF2_LEN_RANGE.end()
+                ));
+            }
+        }
+
+        args.get(0).unwrap().set_type_coercion(SqlType::String);
+        args.get(1).unwrap().set_type_coercion(SqlType::String);
+        args.get(2).unwrap().set_type_coercion(SqlType::Int);
+        args.get(3).unwrap().set_type_coercion(SqlType::Int);
+
+        Ok(Self { ret: String::new() })
+    }
+
+    fn process<'a>(
+        &'a mut self,
+        _cfg: &UdfCfg<Process>,
+        args: &ArgList<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<Self::Returns<'a>, ProcessError> {
+        let password = args.get(0).unwrap().value();
+        let salt = args.get(1).unwrap().value();
+        let (Some(password), Some(salt), Some(iterations), Some(len)) = (
+            password.as_bytes(),
+            salt.as_bytes(),
+            args.get(2).unwrap().value().as_int(),
+            args.get(3).unwrap().value().as_int(),
+        ) else {
+            return Ok(None);
+        };
+
+        if !PBKDF2_SALT_RANGE.contains(&salt.len()) || !PBKDF2_LEN_RANGE.contains(&len) {
+            return Ok(None);
+        }
+        if !(1..=PBKDF2_MAX_ITERATIONS).contains(&iterations) {
+            return Ok(None);
+        }
+        let rounds = iterations as u32;
+
+        let params = pbkdf2::Params {
+            rounds,
+            output_length: len as usize,
+        };
+        let salt = SaltString::encode_b64(salt).map_err(|_| ProcessError)?;
+        let hash = Pbkdf2
+            .hash_password_customized(
+                password,
+                Some(Ident::new_unwrap(PBKDF2_SHA256)),
+                None,
+                params,
+                Salt::from(&salt),
+            )
+            .map_err(|_| ProcessError)?;
+        self.ret.clear();
+        write!(self.ret, "{hash}").unwrap();
+        Ok(Some(&self.ret))
+    }
+}
+
+/// Check a password against a stored hash from any of the functions above
+#[derive(Debug)]
+struct PasswordVerify;
+
+#[register(name = "password_verify")]
+impl BasicUdf for PasswordVerify {
+    type Returns<'a> = Option<i64>;
+
+    fn init(_cfg: &UdfCfg<Init>, args: &ArgList<Init>) -> Result<Self, String> {
+        if args.len() != 2 {
+            return Err(format!(
+                "password_verify takes 2 arguments but got {} \
+                (usage: `password_verify(password, hash)`)",
+                args.len()
+            ));
+        }
+        args.get(0).unwrap().set_type_coercion(SqlType::String);
+        args.get(1).unwrap().set_type_coercion(SqlType::String);
+        Ok(Self)
+    }
+
+    fn process<'a>(
+        &'a mut self,
+        _cfg: &UdfCfg<Process>,
+        args: &ArgList<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<Self::Returns<'a>, ProcessError> {
+        let password = args.get(0).unwrap().value();
+        let hash = args.get(1).unwrap().value();
+        let (Some(password), Some(hash)) = (password.as_bytes(), hash.as_string()) else {
+            return Ok(None);
+        };
+
+        Ok(Some(verify_any(password, hash).into()))
+    }
+}
+
+/// Verify a password against a bcrypt or PHC hash. Malformed hashes never match.
+fn verify_any(password: &[u8], hash: &str) -> bool {
+    if ["$2a$", "$2b$", "$2x$", "$2y$"]
+        .iter()
+        .any(|prefix| hash.starts_with(prefix))
+    {
+        // Longer passwords would be truncated, and match any with the same prefix
+        if !bcrypt_within_limits(hash) || password.len() > BCRYPT_MAX_PASSWORD_LEN {
+            return false;
+        }
+        return bcrypt::verify(password, hash).unwrap_or(false);
+    }
+
+    let Ok(parsed) = PasswordHash::new(hash) else {
+        return false;
+    };
+    if !within_limits(&parsed) {
+        return false;
+    }
+    let verifiers: [&dyn PasswordVerifier; 3] = [&Argon2::default(), &Scrypt, &Pbkdf2];
+    parsed.verify_password(&verifiers, password).is_ok()
+}
+
+/// Check the two digit cost in a `$2b$NN$...` hash
+fn bcrypt_within_limits(hash: &str) -> bool {
+    let cost = hash
+        .get(4..6)
+        .filter(|cost| cost.bytes().all(|b| b.is_ascii_digit()))
+        .and_then(|cost| cost.parse::<i64>().ok());
+    matches!(cost, Some(cost) if cost <= BCRYPT_MAX_COST)
+}
+
+/// Check the cost parameters of a stored hash before verifying against it, since
+/// they come from data rather than from the query
+fn within_limits(hash: &PasswordHash) -> bool {
+    let fn_name = "password_verify";
+    match hash.algorithm.as_str() {
+        "argon2d" | "argon2i" | "argon2id" => argon2::Params::try_from(hash).map_or(false, |p| {
+            argon2_limits(fn_name, p.m_cost(), p.t_cost(), p.p_cost()).is_ok()
+        }),
+        "scrypt" => scrypt::Params::try_from(hash).map_or(false, |p| {
+            scrypt_limits(fn_name, p.log_n().into(), p.r(), p.p()).is_ok()
+        }),
+        _ => pbkdf2::Params::try_from(hash)
+            .map_or(false, |p| i64::from(p.rounds) <= PBKDF2_MAX_ITERATIONS),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use udf::mock::*;
+
+    use super::*;
+
+    /// Run a hashing UDF that takes a password and optional params
+    fn run<U: BasicUdf>(mut args: MockArgList) -> String
+    where
+        for<'a> U::Returns<'a>: Into<Option<&'a str>>,
+    {
+        let mut cfg = MockUdfCfg::new();
+        let mut udf = U::init(cfg.as_init(), args.as_init()).unwrap();
+        let res = udf
+            .process(cfg.as_process(), args.as_process(), None)
+            .unwrap();
+        res.into().unwrap().to_owned()
+    }
+
+    #[test]
+    fn test_argon2id() {
+        let hash = run::<Argon2idHash>(mock_args![
+            ("hunter2", "", false),
+            ("m=64,t=1,p=1", "", false)
+        ]);
+        assert!(hash.starts_with("$argon2id$v=19$m=64,t=1,p=1$"), "{hash}");
+        assert!(verify_any(b"hunter2", &hash));
+        assert!(!verify_any(b"hunter3", &hash));
+    }
+
+    #[test]
+    fn test_scrypt() {
+        let hash = run::<ScryptHash>(mock_args![("hunter2", "", false), ("ln=4", "", false)]);
+        assert!(hash.starts_with("$scrypt$ln=4,r=8,p=1$"), "{hash}");
+        assert!(verify_any(b"hunter2", &hash));
+        assert!(!verify_any(b"hunter3", &hash));
+    }
+
+    #[test]
+    fn test_bcrypt() {
+        let hash = run::<BcryptHash>(mock_args![("hunter2", "", false), (Int 4, "", false)]);
+        assert!(hash.starts_with("$2b$04$"), "{hash}");
+        assert!(verify_any(b"hunter2", &hash));
+        assert!(!verify_any(b"hunter3", &hash));
+    }
+
+    #[test]
+    fn test_bcrypt_long_password() {
+        let long = "x".repeat(BCRYPT_MAX_PASSWORD_LEN);
+        let hash = run::<BcryptHash>(mock_args![(long.as_str(), "", false), (Int 4, "", false)]);
+        assert!(verify_any(long.as_bytes(), &hash));
+
+        // One byte more would be truncated to the same password
+        let longer = format!("{long}y");
+        assert!(!verify_any(longer.as_bytes(), &hash));
+
+        let mut cfg = MockUdfCfg::new();
+        let mut args = mock_args![(longer.as_str(), "", false), (Int 4, "", false)];
+        let mut udf = BcryptHash::init(cfg.as_init(), args.as_init()).unwrap();
+        let res = udf
+            .process(cfg.as_process(), args.as_process(), None)
+            .unwrap();
+        assert_eq!(res, None);
+    }
+
+    #[test]
+    fn test_pbkdf2() {
+        // RFC 7914 section 11 test vector
+        let hash = run::<Pbkdf2Sha256>(mock_args![
+            ("passwd", "", false),
+            ("salt", "", false),
+            (Int 1, "", false),
+            (Int 64, "", false)
+        ]);
+        assert_eq!(
+            hash,
+            "$pbkdf2-sha256$i=1,l=64$c2FsdA$VawEblbjCJ/sFpHCJUS2BflBhSFt3gRl5oudV8INrLxJypzM8Xm2RZkWZLOdd+8xfHG4RbHjC9UJESBB06GXgw"
+        );
+        assert!(verify_any(b"passwd", &hash));
+        assert!(!verify_any(b"passwd2", &hash));
+    }
+
+    #[test]
+    fn test_init_errors() {
+        let mut cfg = MockUdfCfg::new();
+
+        let mut args = mock_args![("pw", "", false), ("m=64,x=1", "", false)];
+        let err = Argon2idHash::init(cfg.as_init(), args.as_init()).unwrap_err();
+        assert_eq!(
+            err,
+            "argon2id_hash: unknown parameter 'x'; expected one of m, t, p"
+        );
+
+        let mut args = mock_args![("pw", "", false), (Int 50, "", false)];
+        assert!(BcryptHash::init(cfg.as_init(), args.as_init()).is_err());
+
+        let mut args = mock_args![
+            ("pw", "", false),
+            ("salt", "", false),
+            (Int 1000, "", false),
+            (Int 5, "", false)
+        ];
+        assert!(Pbkdf2Sha256::init(cfg.as_init(), args.as_init()).is_err());
+    }
+
+    #[test]
+    fn test_cost_limits() {
+        let mut cfg = MockUdfCfg::new();
+
+        let mut args = mock_args![("pw", "", false), ("m=4194304,t=1,p=1", "", false)];
+        let err = Argon2idHash::init(cfg.as_init(), args.as_init()).unwrap_err();
+        assert_eq!(
+            err,
+            "argon2id_hash: parameter 'm' may be at most 1048576, got 4194304"
+        );
+        for params in ["t=4294967295", "p=255"] {
+            let mut args = mock_args![("pw", "", false), (params, "", false)];
+            assert!(Argon2idHash::init(cfg.as_init(), args.as_init()).is_err());
+        }
+
+        for params in ["ln=40", "ln=21", "ln=20,r=16", "p=17"] {
+            let mut args = mock_args![("pw", "", false), (params, "", false)];
+            assert!(
+                ScryptHash::init(cfg.as_init(), args.as_init()).is_err(),
+                "{params}"
+            );
+        }
+        assert!(scrypt_params(b"ln=20,r=8,p=16").is_ok());
+
+        let mut args = mock_args![
+            ("pw", "", false),
+            ("salt", "", false),
+            (Int 4_294_967_295, "", false),
+            (Int 64, "", false)
+        ];
+        assert!(Pbkdf2Sha256::init(cfg.as_init(), args.as_init()).is_err());
+
+        // Stored hashes with huge costs are rejected without being computed
+        for hash in [
+            "$argon2id$v=19$m=4194304,t=1,p=1$c2FsdHNhbHQ$YWJjZGVmZ2hpamtsbW5vcHFyc3R1dnd4eXo",
+            "$argon2id$v=19$m=64,t=4294967295,p=1$c2FsdHNhbHQ$YWJjZGVmZ2hpamtsbW5vcHFyc3R1dnd4eXo",
+            "$scrypt$ln=40,r=8,p=1$c2FsdHNhbHQ$YWJjZGVmZ2hpamtsbW5vcHFyc3R1dnd4eXo",
+            "$pbkdf2-sha256$i=4294967295,l=32$c2FsdA$VawEblbjCJ/sFpHCJUS2BflBhSFt3gRl5oudV8INrLw",
+        ] {
+            let parsed = PasswordHash::new(hash).unwrap();
+            assert!(!within_limits(&parsed), "{hash}");
+            assert!(!verify_any(b"pw", hash));
+        }
+
+        // The same for bcrypt, which is checked before parsing the rest of the hash
+        let mut args = mock_args![("pw", "", false), (Int 17, "", false)];
+        assert!(BcryptHash::init(cfg.as_init(), args.as_init()).is_err());
+        let hash = run::<BcryptHash>(mock_args![("pw", "", false), (Int 4, "", false)]);
+        assert!(verify_any(b"pw", &hash));
+        for cost in ["17", "31", "x1"] {
+            let hash = hash.replacen("$04$", &format!("${cost}$"), 1);
+            assert!(!bcrypt_within_limits(&hash), "{hash}");
+            assert!(!verify_any(b"pw", &hash), "{hash}");
+        }
+
+        let ok = "$pbkdf2-sha256$i=1,l=32$c2FsdA$VawEblbjCJ/sFpHCJUS2BflBhSFt3gRl5oudV8INrLw";
+        assert!(within_limits(&PasswordHash::new(ok).unwrap()));
+        assert!(verify_any(b"passwd", ok));
+    }
+
+    #[test]
+    fn test_verify_malformed() {
+        assert!(!verify_any(b"pw", "not a hash"));
+        assert!(!verify_any(b"pw", "$2b$garbage"));
+        assert!(!verify_any(b"pw", "$unknown$v=1$abc"));
+    }
+}
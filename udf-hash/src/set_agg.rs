@@ -0,0 +1,216 @@
+//! Order-independent aggregate hashes
+//!
+//! Each row is hashed on its own, then the row digests are combined using wrapping
+//! addition. Addition is commutative so the result does not depend on the order that
+//! rows are processed in, and (unlike XOR) duplicate rows do not cancel each other out.
+//!
+//! This makes the result a hash of a multiset: a duplicate row changes it just like
+//! any other row. A sum of hashes is not collision resistant, since any rows whose
+//! digests add up to the same total collide. That is easy to arrange with
+//! `xxhash64_set_agg`'s 64 bits, so it only detects accidental differences.
+//! `blake3_set_agg` sums 256-bit values, which makes chance collisions negligible,
+//! but neither should be used where rows may be chosen to forge a match.
+
+use udf::prelude::*;
+use xxhash_rust::xxh64::Xxh64;
+
+use crate::hash_arg;
+
+/// Sum of the xxhash64 of each row. Only 64 bits, so see the module docs before
+/// relying on it.
+struct XxHash64SetAgg {
+    hasher: Xxh64,
+    sum: u64,
+}
+
+#[register(name = "xxhash64_set_agg", alias = "xxhash_set_agg")]
+impl BasicUdf for XxHash64SetAgg {
+    type Returns<'a> = i64;
+
+    fn init(_cfg: &UdfCfg<Init>, _args: &ArgList<Init>) -> Result<Self, String> {
+        Ok(Self {
+            hasher: Xxh64::new(0),
+            sum: 0,
+        })
+    }
+
+    fn process<'a>(
+        &'a mut self,
+        _cfg: &UdfCfg<Process>,
+        _args: &ArgList<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<Self::Returns<'a>, ProcessError> {
+        Ok(self.sum as i64)
+    }
+}
+
+#[register(name = "xxhash64_set_agg", alias = "xxhash_set_agg")]
+impl AggregateUdf for XxHash64SetAgg {
+    fn clear(
+        &mut self,
+        _cfg: &UdfCfg<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<(), NonZeroU8> {
+        self.sum = 0;
+        Ok(())
+    }
+
+    fn add(
+        &mut self,
+        _cfg: &UdfCfg<Process>,
+        args: &ArgList<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<(), NonZeroU8> {
+        self.hasher.reset(0);
+        args.iter()
+            .for_each(|arg| hash_arg(arg, |buf| self.hasher.update(buf)));
+        self.sum = self.sum.wrapping_add(self.hasher.digest());
+        Ok(())
+    }
+}
+
+/// Sum of the blake3 hash of each row, as 256-bit integers
+struct Blake3SetAgg {
+    hasher: blake3::Hasher,
+    sum: [u8; 32],
+    hex: [u8; 64],
+}
+
+#[register(name = "blake3_set_agg")]
+impl BasicUdf for Blake3SetAgg {
+    type Returns<'a> = &'a [u8];
+
+    fn init(_cfg: &UdfCfg<Init>, _args: &ArgList<Init>) -> Result<Self, String> {
+        Ok(Self {
+            hasher: blake3::Hasher::new(),
+            sum: [0u8; 32],
+            hex: [0u8; 64],
+        })
+    }
+
+    fn process<'a>(
+        &'a mut self,
+        _cfg: &UdfCfg<Process>,
+        _args: &ArgList<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<Self::Returns<'a>, ProcessError> {
+        data_encoding::HEXUPPER.encode_mut(&self.sum, &mut self.hex);
+        Ok(&self.hex)
+    }
+}
+
+#[register(name = "blake3_set_agg")]
+impl AggregateUdf for Blake3SetAgg {
+    fn clear(
+        &mut self,
+        _cfg: &UdfCfg<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<(), NonZeroU8> {
+        self.sum = [0u8; 32];
+        Ok(())
+    }
+
+    fn add(
+        &mut self,
+        _cfg: &UdfCfg<Process>,
+        args: &ArgList<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<(), NonZeroU8> {
+        blake3_add_row(&mut self.hasher, &mut self.sum, args);
+        Ok(())
+    }
+}
+
+struct Blake3SetAggBin {
+    hasher: blake3::Hasher,
+    sum: [u8; 32],
+}
+
+#[register(name = "blake3_set_agg_bin")]
+impl BasicUdf for Blake3SetAggBin {
+    type Returns<'a> = &'a [u8];
+
+    fn init(_cfg: &UdfCfg<Init>, _args: &ArgList<Init>) -> Result<Self, String> {
+        Ok(Self {
+            hasher: blake3::Hasher::new(),
+            sum: [0u8; 32],
+        })
+    }
+
+    fn process<'a>(
+        &'a mut self,
+        _cfg: &UdfCfg<Process>,
+        _args: &ArgList<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<Self::Returns<'a>, ProcessError> {
+        Ok(&self.sum)
+    }
+}
+
+#[register(name = "blake3_set_agg_bin")]
+impl AggregateUdf for Blake3SetAggBin {
+    fn clear(
+        &mut self,
+        _cfg: &UdfCfg<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<(), NonZeroU8> {
+        self.sum = [0u8; 32];
+        Ok(())
+    }
+
+    fn add(
+        &mut self,
+        _cfg: &UdfCfg<Process>,
+        args: &ArgList<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<(), NonZeroU8> {
+        blake3_add_row(&mut self.hasher, &mut self.sum, args);
+        Ok(())
+    }
+}
+
+/// Hash a single row and add it to the running sum
+fn blake3_add_row(hasher: &mut blake3::Hasher, sum: &mut [u8; 32], args: &ArgList<Process>) {
+    hasher.reset();
+    args.iter().for_each(|arg| {
+        hash_arg(arg, |buf| {
+            hasher.update(buf);
+        })
+    });
+    wrapping_add_be(sum, hasher.finalize().as_bytes());
+}
+
+/// Add two big endian integers of the same size, discarding any overflow
+fn wrapping_add_be<const N: usize>(acc: &mut [u8; N], val: &[u8; N]) {
+    let mut carry = 0u16;
+    for (a, v) in acc.iter_mut().zip(val).rev() {
+        let sum = u16::from(*a) + u16::from(*v) + carry;
+        *a = sum as u8;
+        carry = sum >> 8;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wrapping_add_be() {
+        let mut acc = [0x00, 0xff, 0xff];
+        wrapping_add_be(&mut acc, &[0x00, 0x00, 0x01]);
+        assert_eq!(acc, [0x01, 0x00, 0x00]);
+
+        // Overflow wraps around
+        let mut acc = [0xff, 0xff, 0xff];
+        wrapping_add_be(&mut acc, &[0x00, 0x00, 0x02]);
+        assert_eq!(acc, [0x00, 0x00, 0x01]);
+
+        // Order does not matter
+        let (a, b, c) = ([0x12, 0x34, 0x56], [0xfe, 0xdc, 0xba], [0x80, 0x00, 0x01]);
+        let mut acc1 = [0u8; 3];
+        let mut acc2 = [0u8; 3];
+        [a, b, c].iter().for_each(|v| wrapping_add_be(&mut acc1, v));
+        [c, a, b].iter().for_each(|v| wrapping_add_be(&mut acc2, v));
+        assert_eq!(acc1, acc2);
+    }
+}
@@ -0,0 +1,437 @@
+//! SHA-3 extendable output functions, and the NIST SP 800-185 derived functions
+//!
+//! `shake128(length, data...)` and `shake256(length, data...)` produce `length` bytes
+//! of output. Like `blake3_xof`, each is available as hex or binary.
+//!
+//! The SP 800-185 functions also take an output length, and an optional
+//! customization string that gives domain separation. A `NULL` customization is
+//! the same as an empty one.
+//!
+//! - `tuplehash128(length, data...)` and `tuplehash256` hash each argument as a
+//!   separate tuple element, so argument boundaries affect the result
+//! - `kmac128(key, customization, length, data...)` and `kmac256` are MACs
+//! - `cshake256(customization, length, data...)`
+//! - `k12(length, data[, customization])` is KangarooTwelve
+
+use digest::{ExtendableOutput, Update, XofReader};
+use sha3::{Shake128, Shake256};
+use tiny_keccak::{CShake, Hasher, KangarooTwelve, Kmac, TupleHash};
+use udf::prelude::*;
+
+use crate::hash_arg;
+
+/// Largest output that the XOFs will produce (1 MiB)
+const MAX_XOF_LEN: i64 = 1 << 20;
+
+/// Validate the output length argument, returning the maximum output length
+fn xof_len_init(args: &ArgList<Init>, idx: usize, fn_name: &str) -> Result<i64, String> {
+    let mut arg = args.get(idx).unwrap();
+    let mut max_len = MAX_XOF_LEN;
+
+    // Values must be checked before setting coercion
+    if let Some(len) = arg.value().as_int() {
+        if !(0..=MAX_XOF_LEN).contains(&len) {
+            return Err(format!(
+                "{fn_name} output length must be between 0 and {MAX_XOF_LEN}, got {len}"
+            ));
+        }
+        max_len = len;
+    }
+    arg.set_type_coercion(SqlType::Int);
+    Ok(max_len)
+}
+
+/// Get the output length for this row, `None` if it is invalid
+fn xof_len_arg(arg: SqlArg<Process>) -> Option<usize> {
+    let len = arg.value().as_int()?;
+    (0..=MAX_XOF_LEN)
+        .contains(&len)
+        .then_some(len.unsigned_abs() as usize)
+}
+
+/// Output buffers shared by the XOF functions
+#[derive(Debug, Default)]
+struct XofOutput {
+    hex: bool,
+    ret: Vec<u8>,
+    hex_ret: Vec<u8>,
+}
+
+impl XofOutput {
+    fn new(cfg: &UdfCfg<Init>, max_len: i64, hex: bool) -> Self {
+        let max_len = if hex { max_len * 2 } else { max_len };
+        cfg.set_max_len(max_len.unsigned_abs());
+        Self {
+            hex,
+            ..Default::default()
+        }
+    }
+
+    /// Read `len` bytes from the reader and return them in the configured format
+    fn fill(&mut self, mut reader: impl XofReader, len: usize) -> &[u8] {
+        self.ret.resize(len, 0);
+        reader.read(&mut self.ret);
+        self.output()
+    }
+
+    /// Finalize a fixed-length hasher with `len` bytes of output
+    fn finalize(&mut self, hasher: impl Hasher, len: usize) -> &[u8] {
+        self.ret.resize(len, 0);
+        hasher.finalize(&mut self.ret);
+        self.output()
+    }
+
+    /// Return `ret` in the configured format
+    fn output(&mut self) -> &[u8] {
+        let len = self.ret.len();
+        if !self.hex {
+            return &self.ret;
+        }
+
+        self.hex_ret.resize(len * 2, 0);
+        data_encoding::HEXUPPER.encode_mut(&self.ret, &mut self.hex_ret);
+        &self.hex_ret
+    }
+}
+
+// Create a SHAKE UDF named `$fn_name` using `$hash_ty`
+macro_rules! shake_udf {
+    ($fn_name:ident, $hash_ty:ty, hex: $hex:expr) => {
+        #[allow(non_camel_case_types)]
+        #[derive(Debug)]
+        struct $fn_name {
+            out: XofOutput,
+        }
+
+        #[register]
+        impl BasicUdf for $fn_name {
+            type Returns<'a> = Option<&'a [u8]>;
+
+            fn init(cfg: &UdfCfg<Init>, args: &ArgList<Init>) -> Result<Self, String> {
+                if args.is_empty() {
+                    return Err(format!(
+                        "{} takes at least 1 argument but got 0 (usage: `{}(length, data...)`)",
+                        stringify!($fn_name),
+                        stringify!($fn_name),
+                    ));
+                }
+                let max_len = xof_len_init(args, 0, stringify!($fn_name))?;
+                Ok(Self {
+                    out: XofOutput::new(cfg, max_len, $hex),
+                })
+            }
+
+            fn process<'a>(
+                &'a mut self,
+                _cfg: &UdfCfg<Process>,
+                args: &ArgList<Process>,
+                _error: Option<NonZeroU8>,
+            ) -> Result<Self::Returns<'a>, ProcessError> {
+                let Some(len) = xof_len_arg(args.get(0).unwrap()) else {
+                    return Ok(None);
+                };
+                let mut hasher = <$hash_ty>::default();
+                args.iter()
+                    .skip(1)
+                    .for_each(|arg| hash_arg(arg, |buf| Update::update(&mut hasher, buf)));
+                Ok(Some(self.out.fill(hasher.finalize_xof(), len)))
+            }
+        }
+    };
+}
+
+shake_udf!(shake128, Shake128, hex: true);
+shake_udf!(shake128_bin, Shake128, hex: false);
+shake_udf!(shake256, Shake256, hex: true);
+shake_udf!(shake256_bin, Shake256, hex: false);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Sp800Mode {
+    TupleHash128,
+    TupleHash256,
+    Kmac128,
+    Kmac256,
+    CShake256,
+    K12,
+}
+
+impl Sp800Mode {
+    fn usage(self) -> &'static str {
+        match self {
+            Self::TupleHash128 | Self::TupleHash256 => "length, data...",
+            Self::Kmac128 | Self::Kmac256 => "key, customization, length, data...",
+            Self::CShake256 => "customization, length, data...",
+            Self::K12 => "length, data[, customization]",
+        }
+    }
+
+    /// Index of the length argument
+    fn len_idx(self) -> usize {
+        match self {
+            Self::TupleHash128 | Self::TupleHash256 | Self::K12 => 0,
+            Self::CShake256 => 1,
+            Self::Kmac128 | Self::Kmac256 => 2,
+        }
+    }
+
+    /// Allowed argument counts
+    fn arg_count(self) -> (usize, Option<usize>) {
+        match self {
+            Self::TupleHash128 | Self::TupleHash256 => (1, None),
+            Self::Kmac128 | Self::Kmac256 => (3, None),
+            Self::CShake256 => (2, None),
+            Self::K12 => (2, Some(3)),
+        }
+    }
+}
+
+/// Shared implementation for the SP 800-185 functions
+#[derive(Debug)]
+struct Sp800Ext {
+    mode: Sp800Mode,
+    out: XofOutput,
+}
+
+impl Sp800Ext {
+    fn init(
+        cfg: &UdfCfg<Init>,
+        args: &ArgList<Init>,
+        fn_name: &str,
+        mode: Sp800Mode,
+        hex: bool,
+    ) -> Result<Self, String> {
+        let (min, max) = mode.arg_count();
+        if args.len() < min || max.map_or(false, |max| args.len() > max) {
+            let expected = match max {
+                Some(max) => format!("{min} or {max} arguments"),
+                None => format!("at least {min} arguments"),
+            };
+            return Err(format!(
+                "{fn_name} takes {expected} but got {} (usage: `{fn_name}({})`)",
+                args.len(),
+                mode.usage()
+            ));
+        }
+
+        let max_len = xof_len_init(args, mode.len_idx(), fn_name)?;
+        let string_args: &[usize] = match mode {
+            Sp800Mode::TupleHash128 | Sp800Mode::TupleHash256 => &[],
+            Sp800Mode::Kmac128 | Sp800Mode::Kmac256 => &[0, 1],
+            Sp800Mode::CShake256 => &[0],
+            Sp800Mode::K12 => &[2],
+        };
+        for idx in string_args {
+            if let Some(mut arg) = args.get(*idx) {
+                arg.set_type_coercion(SqlType::String);
+            }
+        }
+
+        Ok(Self {
+            mode,
+            out: XofOutput::new(cfg, max_len, hex),
+        })
+    }
+
+    fn process(&mut self, args: &ArgList<Process>) -> Option<&[u8]> {
+        let len = xof_len_arg(args.get(self.mode.len_idx()).unwrap())?;
+        // A missing or `NULL` customization is empty
+        let string_arg = |idx: usize| args.get(idx).map(|arg| arg.value());
+        let custom = |value: &Option<SqlResult<'_>>| -> Vec<u8> {
+            value
+                .as_ref()
+                .and_then(SqlResult::as_bytes)
+                .unwrap_or_default()
+                .to_owned()
+        };
+
+        let ret = match self.mode {
+            Sp800Mode::TupleHash128 | Sp800Mode::TupleHash256 => {
+                let mut hasher = if self.mode == Sp800Mode::TupleHash128 {
+                    TupleHash::v128(&[])
+                } else {
+                    TupleHash::v256(&[])
+                };
+                // Each argument is one tuple element
+                args.iter()
+                    .skip(1)
+                    .for_each(|arg| hash_arg(arg, |buf| hasher.update(buf)));
+                self.out.finalize(hasher, len)
+            }
+            Sp800Mode::Kmac128 | Sp800Mode::Kmac256 => {
+                let key = string_arg(0).unwrap();
+                let key = key.as_bytes()?;
+                let custom = custom(&string_arg(1));
+                let mut hasher = if self.mode == Sp800Mode::Kmac128 {
+                    Kmac::v128(key, &custom)
+                } else {
+                    Kmac::v256(key, &custom)
+                };
+                args.iter()
+                    .skip(3)
+                    .for_each(|arg| hash_arg(arg, |buf| hasher.update(buf)));
+                self.out.finalize(hasher, len)
+            }
+            Sp800Mode::CShake256 => {
+                let mut hasher = CShake::v256(&[], &custom(&string_arg(0)));
+                args.iter()
+                    .skip(2)
+                    .for_each(|arg| hash_arg(arg, |buf| hasher.update(buf)));
+                self.out.finalize(hasher, len)
+            }
+            Sp800Mode::K12 => {
+                let mut hasher = KangarooTwelve::new(custom(&string_arg(2)));
+                hash_arg(args.get(1).unwrap(), |buf| hasher.update(buf));
+                self.out.finalize(hasher, len)
+            }
+        };
+        Some(ret)
+    }
+}
+
+// Create an SP 800-185 UDF named `$fn_name` with the given mode and output
+macro_rules! sp800_udf {
+    ($fn_name:ident, $mode:expr, hex: $hex:expr) => {
+        #[allow(non_camel_case_types)]
+        #[derive(Debug)]
+        struct $fn_name {
+            ext: Sp800Ext,
+        }
+
+        #[register]
+        impl BasicUdf for $fn_name {
+            type Returns<'a> = Option<&'a [u8]>;
+
+            fn init(cfg: &UdfCfg<Init>, args: &ArgList<Init>) -> Result<Self, String> {
+                Sp800Ext::init(cfg, args, stringify!($fn_name), $mode, $hex).map(|ext| Self { ext })
+            }
+
+            fn process<'a>(
+                &'a mut self,
+                _cfg: &UdfCfg<Process>,
+                args: &ArgList<Process>,
+                _error: Option<NonZeroU8>,
+            ) -> Result<Self::Returns<'a>, ProcessError> {
+                Ok(self.ext.process(args))
+            }
+        }
+    };
+}
+
+sp800_udf!(tuplehash128, Sp800Mode::TupleHash128, hex: true);
+sp800_udf!(tuplehash128_bin, Sp800Mode::TupleHash128, hex: false);
+sp800_udf!(tuplehash256, Sp800Mode::TupleHash256, hex: true);
+sp800_udf!(tuplehash256_bin, Sp800Mode::TupleHash256, hex: false);
+sp800_udf!(kmac128, Sp800Mode::Kmac128, hex: true);
+sp800_udf!(kmac128_bin, Sp800Mode::Kmac128, hex: false);
+sp800_udf!(kmac256, Sp800Mode::Kmac256, hex: true);
+sp800_udf!(kmac256_bin, Sp800Mode::Kmac256, hex: false);
+sp800_udf!(cshake256, Sp800Mode::CShake256, hex: true);
+sp800_udf!(cshake256_bin, Sp800Mode::CShake256, hex: false);
+sp800_udf!(k12, Sp800Mode::K12, hex: true);
+sp800_udf!(k12_bin, Sp800Mode::K12, hex: false);
+
+#[cfg(test)]
+mod tests {
+    use udf::mock::*;
+
+    use super::*;
+
+    #[test]
+    fn test_shake() {
+        let mut cfg = MockUdfCfg::new();
+
+        // FIPS 202 values for the empty message
+        let mut args = mock_args![(Int 32, "", false)];
+        let mut udf = shake128::init(cfg.as_init(), args.as_init()).unwrap();
+        let res = udf
+            .process(cfg.as_process(), args.as_process(), None)
+            .unwrap();
+        assert_eq!(
+            res.unwrap(),
+            b"7F9C2BA4E88F827D616045507605853ED73B8093F6EFBC88EB1A6EACFA66EF26"
+        );
+
+        let mut udf = shake256_bin::init(cfg.as_init(), args.as_init()).unwrap();
+        let res = udf
+            .process(cfg.as_process(), args.as_process(), None)
+            .unwrap();
+        assert_eq!(
+            data_encoding::HEXLOWER.encode(res.unwrap()),
+            "46b9dd2b0ba88d13233b3feb743eeb243fcd52ea62b81b82b50c27646ed5762f"
+        );
+
+        let mut args = mock_args![(Int 1_048_577, "", false)];
+        assert!(shake128::init(cfg.as_init(), args.as_init()).is_err());
+    }
+
+    /// Run a binary UDF and return lowercase hex
+    fn run_bin<U: BasicUdf>(mut args: MockArgList) -> String
+    where
+        for<'a> U::Returns<'a>: Into<Option<&'a [u8]>>,
+    {
+        let mut cfg = MockUdfCfg::new();
+        let mut udf = U::init(cfg.as_init(), args.as_init()).unwrap();
+        let res = udf
+            .process(cfg.as_process(), args.as_process(), None)
+            .unwrap();
+        data_encoding::HEXLOWER.encode(res.into().unwrap())
+    }
+
+    #[test]
+    fn test_sp800_185() {
+        // Samples from NIST SP 800-185
+        let key: Vec<u8> = (0x40..0x60).collect();
+        let key = std::str::from_utf8(&key).unwrap();
+        assert_eq!(
+            run_bin::<kmac128_bin>(mock_args![
+                (key, "", false),
+                ("", "", false),
+                (Int 32, "", false),
+                ("\x00\x01\x02\x03", "", false)
+            ]),
+            "e5780b0d3ea6f7d3a429c5706aa43a00fadbd7d49628839e3187243f456ee14e"
+        );
+
+        assert_eq!(
+            run_bin::<tuplehash128_bin>(mock_args![
+                (Int 32, "", false),
+                ("\x00\x01\x02", "", false),
+                ("\x10\x11\x12\x13\x14\x15", "", false)
+            ]),
+            "c5d8786c1afb9b82111ab34b65b2c0048fa64e6d48e263264ce1707d3ffc8ed1"
+        );
+
+        assert_eq!(
+            run_bin::<cshake256_bin>(mock_args![
+                ("Email Signature", "", false),
+                (Int 64, "", false),
+                ("\x00\x01\x02\x03", "", false)
+            ]),
+            "d008828e2b80ac9d2218ffee1d070c48b8e4c87bff32c9699d5b6896eee0edd1\
+            64020e2be0560858d9c00c037e34a96937c561a74c412bb4c746469527281c8c"
+        );
+
+        // KangarooTwelve of the empty message
+        assert_eq!(
+            run_bin::<k12_bin>(mock_args![(Int 32, "", false), ("", "", false)]),
+            "1ac2d450fc3b4205d19da7bfca1b37513c0803577ac7167f06fe2ce1f0ef39e5"
+        );
+    }
+
+    #[test]
+    fn test_sp800_185_args() {
+        let mut cfg = MockUdfCfg::new();
+        let mut args = mock_args![(Int 32, "", false)];
+        assert_eq!(
+            k12::init(cfg.as_init(), args.as_init()).err().unwrap(),
+            "k12 takes 2 or 3 arguments but got 1 (usage: `k12(length, data[, customization])`)"
+        );
+
+        let mut args = mock_args![("key", "", false), ("", "", false)];
+        assert_eq!(
+            kmac256::init(cfg.as_init(), args.as_init()).err().unwrap(),
+            "kmac256 takes at least 3 arguments but got 2 \
+            (usage: `kmac256(key, customization, length, data...)`)"
+        );
+    }
+}
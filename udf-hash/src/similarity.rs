@@ -0,0 +1,328 @@
+//! Similarity signatures for near-duplicate detection
+//!
+//! Text is split into overlapping shingles of `n` characters (UTF-8 characters if
+//! the text is valid, otherwise bytes), and each shingle is hashed with xxhash64.
+//! Text shorter than `n` characters is a single shingle.
+//!
+//! - `minhash(text, num_perm, shingle_size)` returns `num_perm` little endian `u64`s,
+//!   the minimum of `xxhash64_seed(i, shingle)` over all shingles for each `i`.
+//!   Empty text has no shingles, so every value is `u64::MAX`.
+//! - `minhash_similarity(a, b)` is the fraction of matching values, which estimates
+//!   the Jaccard similarity of the two shingle sets.
+//! - `simhash64(text[, shingle_size])` is a 64-bit Charikar SimHash of the shingles.
+//! - `hamming_distance(a, b)` counts differing bits between two integers, or two
+//!   strings of the same length.
+
+use std::ops::RangeInclusive;
+
+use udf::prelude::*;
+use xxhash_rust::xxh64::xxh64;
+
+const NUM_PERM_RANGE: RangeInclusive<i64> = 1..=1024;
+const SHINGLE_SIZE_RANGE: RangeInclusive<i64> = 1..=64;
+/// Shingle size for `simhash64` if none is given
+const DEFAULT_SHINGLE_SIZE: usize = 4;
+
+/// Overlapping windows of `size` characters, or of `size` bytes if the text is
+/// not valid UTF-8
+fn shingles(text: &[u8], size: usize) -> impl Iterator<Item = &[u8]> {
+    let starts: Vec<usize> = match std::str::from_utf8(text) {
+        Ok(s) => s
+            .char_indices()
+            .map(|(i, _)| i)
+            .chain([text.len()])
+            .collect(),
+        Err(_) => (0..=text.len()).collect(),
+    };
+
+    // `starts` includes the end, so there are `starts.len() - 1` characters
+    let count = if text.is_empty() {
+        0
+    } else {
+        starts.len().saturating_sub(size).max(1)
+    };
+    (0..count).map(move |i| {
+        let end = starts[(i + size).min(starts.len() - 1)];
+        &text[starts[i]..end]
+    })
+}
+
+/// Compute a MinHash signature, writing it as little endian bytes
+fn minhash_into(text: &[u8], num_perm: usize, shingle_size: usize, out: &mut Vec<u8>) {
+    let mut mins = vec![u64::MAX; num_perm];
+    for shingle in shingles(text, shingle_size) {
+        for (seed, min) in mins.iter_mut().enumerate() {
+            *min = (*min).min(xxh64(shingle, seed as u64));
+        }
+    }
+    out.clear();
+    mins.iter()
+        .for_each(|m| out.extend_from_slice(&m.to_le_bytes()));
+}
+
+/// Fraction of equal values in two signatures, `None` if they are not compatible
+fn signature_similarity(a: &[u8], b: &[u8]) -> Option<f64> {
+    if a.is_empty() || a.len() != b.len() || a.len() % 8 != 0 {
+        return None;
+    }
+    let total = a.len() / 8;
+    let equal = a
+        .chunks_exact(8)
+        .zip(b.chunks_exact(8))
+        .filter(|(x, y)| x == y)
+        .count();
+    Some(equal as f64 / total as f64)
+}
+
+/// Each shingle votes on each bit: +1 if its hash has the bit set, -1 otherwise.
+/// Bits with a positive total are set in the result.
+fn simhash(text: &[u8], shingle_size: usize) -> u64 {
+    let mut votes = [0i64; 64];
+    for shingle in shingles(text, shingle_size) {
+        let hash = xxh64(shingle, 0);
+        for (bit, vote) in votes.iter_mut().enumerate() {
+            *vote += if hash & (1 << bit) != 0 { 1 } else { -1 };
+        }
+    }
+    votes
+        .iter()
+        .enumerate()
+        .filter(|(_, v)| **v > 0)
+        .fold(0, |acc, (bit, _)| acc | (1 << bit))
+}
+
+/// Validate a constant integer argument
+fn int_range_init(
+    args: &ArgList<Init>,
+    idx: usize,
+    fn_name: &str,
+    arg_name: &str,
+    range: RangeInclusive<i64>,
+) -> Result<(), String> {
+    let mut arg = args.get(idx).unwrap();
+
+    // Values must be checked before setting coercion
+    if let Some(n) = arg.value().as_int() {
+        if !range.contains(&n) {
+            return Err(format!(
+                "{fn_name}: {arg_name} must be between {} and {}, got {n}",
+                range.start(),
+                range.end()
+            ));
+        }
+    }
+    arg.set_type_coercion(SqlType::Int);
+    Ok(())
+}
+
+/// Get an integer argument for this row, `None` if it is out of range
+fn int_range_arg(arg: SqlArg<Process>, range: RangeInclusive<i64>) -> Option<usize> {
+    let n = arg.value().as_int()?;
+    range.contains(&n).then_some(n as usize)
+}
+
+/// `minhash(text, num_perm, shingle_size)`
+struct MinHash {
+    ret: Vec<u8>,
+}
+
+#[register(name = "minhash")]
+impl BasicUdf for MinHash {
+    type Returns<'a> = Option<&'a [u8]>;
+
+    fn init(cfg: &UdfCfg<Init>, args: &ArgList<Init>) -> Result<Self, String> {
+        if args.len() != 3 {
+            return Err(format!(
+                "minhash takes 3 arguments but got {} \
+                (usage: `minhash(text, num_perm, shingle_size)`)",
+                args.len()
+            ));
+        }
+        args.get(0).unwrap().set_type_coercion(SqlType::String);
+        int_range_init(args, 1, "minhash", "num_perm", NUM_PERM_RANGE)?;
+        int_range_init(args, 2, "minhash", "shingle_size", SHINGLE_SIZE_RANGE)?;
+        cfg.set_max_len(*NUM_PERM_RANGE.end() as u64 * 8);
+        Ok(Self { ret: Vec::new() })
+    }
+
+    fn process<'a>(
+        &'a mut self,
+        _cfg: &UdfCfg<Process>,
+        args: &ArgList<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<Self::Returns<'a>, ProcessError> {
+        let text = args.get(0).unwrap().value();
+        let (Some(text), Some(num_perm), Some(shingle_size)) = (
+            text.as_bytes(),
+            int_range_arg(args.get(1).unwrap(), NUM_PERM_RANGE),
+            int_range_arg(args.get(2).unwrap(), SHINGLE_SIZE_RANGE),
+        ) else {
+            return Ok(None);
+        };
+        minhash_into(text, num_perm, shingle_size, &mut self.ret);
+        Ok(Some(&self.ret))
+    }
+}
+
+/// `minhash_similarity(a, b)`, the estimated Jaccard similarity
+struct MinHashSimilarity;
+
+#[register(name = "minhash_similarity")]
+impl BasicUdf for MinHashSimilarity {
+    type Returns<'a> = Option<f64>;
+
+    fn init(_cfg: &UdfCfg<Init>, args: &ArgList<Init>) -> Result<Self, String> {
+        if args.len() != 2 {
+            return Err(format!(
+                "minhash_similarity takes 2 arguments but got {} \
+                (usage: `minhash_similarity(sig_a, sig_b)`)",
+                args.len()
+            ));
+        }
+        args.iter()
+            .for_each(|mut arg| arg.set_type_coercion(SqlType::String));
+        Ok(Self)
+    }
+
+    fn process<'a>(
+        &'a mut self,
+        _cfg: &UdfCfg<Process>,
+        args: &ArgList<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<Self::Returns<'a>, ProcessError> {
+        let (a, b) = (args.get(0).unwrap().value(), args.get(1).unwrap().value());
+        let (Some(a), Some(b)) = (a.as_bytes(), b.as_bytes()) else {
+            return Ok(None);
+        };
+        Ok(signature_similarity(a, b))
+    }
+}
+
+/// `simhash64(text[, shingle_size])`
+struct SimHash64;
+
+#[register(name = "simhash64")]
+impl BasicUdf for SimHash64 {
+    type Returns<'a> = Option<i64>;
+
+    fn init(_cfg: &UdfCfg<Init>, args: &ArgList<Init>) -> Result<Self, String> {
+        if !(1..=2).contains(&args.len()) {
+            return Err(format!(
+                "simhash64 takes 1 or 2 arguments but got {} \
+                (usage: `simhash64(text[, shingle_size])`)",
+                args.len()
+            ));
+        }
+        args.get(0).unwrap().set_type_coercion(SqlType::String);
+        if args.len() == 2 {
+            int_range_init(args, 1, "simhash64", "shingle_size", SHINGLE_SIZE_RANGE)?;
+        }
+        Ok(Self)
+    }
+
+    fn process<'a>(
+        &'a mut self,
+        _cfg: &UdfCfg<Process>,
+        args: &ArgList<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<Self::Returns<'a>, ProcessError> {
+        let shingle_size = match args.get(1) {
+            Some(arg) => int_range_arg(arg, SHINGLE_SIZE_RANGE),
+            None => Some(DEFAULT_SHINGLE_SIZE),
+        };
+        let text = args.get(0).unwrap().value();
+        let (Some(text), Some(shingle_size)) = (text.as_bytes(), shingle_size) else {
+            return Ok(None);
+        };
+        Ok(Some(simhash(text, shingle_size) as i64))
+    }
+}
+
+/// `hamming_distance(a, b)`
+struct HammingDistance;
+
+#[register(name = "hamming_distance")]
+impl BasicUdf for HammingDistance {
+    type Returns<'a> = Option<i64>;
+
+    fn init(_cfg: &UdfCfg<Init>, args: &ArgList<Init>) -> Result<Self, String> {
+        if args.len() != 2 {
+            return Err(format!(
+                "hamming_distance takes 2 arguments but got {} \
+                (usage: `hamming_distance(a, b)`)",
+                args.len()
+            ));
+        }
+        Ok(Self)
+    }
+
+    fn process<'a>(
+        &'a mut self,
+        _cfg: &UdfCfg<Process>,
+        args: &ArgList<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<Self::Returns<'a>, ProcessError> {
+        let ret = match (args.get(0).unwrap().value(), args.get(1).unwrap().value()) {
+            (SqlResult::Int(Some(a)), SqlResult::Int(Some(b))) => (a ^ b).count_ones(),
+            (SqlResult::String(Some(a)), SqlResult::String(Some(b))) if a.len() == b.len() => {
+                a.iter().zip(b).map(|(x, y)| (x ^ y).count_ones()).sum()
+            }
+            _ => return Ok(None),
+        };
+        Ok(Some(ret.into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shingles() {
+        let s: Vec<_> = shingles(b"abcde", 3).collect();
+        assert_eq!(s, [b"abc", b"bcd", b"cde"]);
+
+        // Short text is one shingle, empty text has none
+        let s: Vec<_> = shingles(b"ab", 3).collect();
+        assert_eq!(s, [b"ab"]);
+        assert_eq!(shingles(b"", 3).count(), 0);
+
+        // Multibyte characters are not split
+        let s: Vec<_> = shingles("añb".as_bytes(), 2).collect();
+        assert_eq!(s, ["añ".as_bytes(), "ñb".as_bytes()]);
+
+        // Invalid UTF-8 is split into bytes, even around valid characters
+        let s: Vec<_> = shingles(b"a\xc3\xb1\xff", 2).collect();
+        assert_eq!(s, [b"a\xc3", b"\xc3\xb1", b"\xb1\xff"]);
+        let s: Vec<_> = shingles(b"\x80ab", 2).collect();
+        assert_eq!(s, [b"\x80a", b"ab"]);
+    }
+
+    #[test]
+    fn test_minhash() {
+        let mut a = Vec::new();
+        let mut b = Vec::new();
+        minhash_into(b"the quick brown fox", 64, 3, &mut a);
+        assert_eq!(a.len(), 64 * 8);
+        minhash_into(b"the quick brown fox", 64, 3, &mut b);
+        assert_eq!(signature_similarity(&a, &b), Some(1.0));
+
+        minhash_into(b"the quick brown cat", 256, 3, &mut a);
+        minhash_into(b"the quick brown fox", 256, 3, &mut b);
+        let sim = signature_similarity(&a, &b).unwrap();
+        // The true Jaccard similarity of the shingle sets is 14/20
+        assert!((sim - 0.7).abs() < 0.1, "{sim}");
+
+        assert_eq!(signature_similarity(&a, &b[..8]), None);
+        assert_eq!(signature_similarity(&[], &[]), None);
+    }
+
+    #[test]
+    fn test_simhash() {
+        let a = simhash(b"the quick brown fox jumps over the lazy dog", 4);
+        let b = simhash(b"the quick brown fox jumps over the lazy cat", 4);
+        let c = simhash(b"lorem ipsum dolor sit amet, consectetur", 4);
+        assert!((a ^ b).count_ones() < (a ^ c).count_ones());
+        assert_eq!(simhash(b"", 4), 0);
+    }
+}
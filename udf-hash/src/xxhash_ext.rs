@@ -0,0 +1,390 @@
+//! Seeded xxhash, xxhash3 with a custom secret, 128-bit xxhash3, and unsigned results
+//!
+//! The seeded functions take the seed as their first argument, followed by the data
+//! to hash, e.g. `xxhash64_seed(42, data...)`. Different seeds give independent hash
+//! functions, which is useful for sketches and sharding. A `NULL` seed returns `NULL`.
+//!
+//! SQL UDFs can't return unsigned integers, so the `_u` functions return the `u64`
+//! digest as a decimal string. They are best created with `RETURNS decimal`.
+
+use std::fmt::Write;
+
+use udf::prelude::*;
+use xxhash_rust::xxh3::{xxh3_64, xxh3_64_with_secret_input, SecretInput, Xxh3};
+use xxhash_rust::xxh32::Xxh32;
+use xxhash_rust::xxh64::{xxh64, Xxh64};
+
+use crate::hash_arg;
+
+/// Shortest secret accepted by xxhash3
+const MIN_SECRET_LEN: usize = 136;
+
+/// Check the argument count and set the seed to be an integer
+fn seed_init(args: &ArgList<Init>, fn_name: &str) -> Result<(), String> {
+    let Some(mut seed) = args.get(0) else {
+        return Err(format!(
+            "{fn_name} takes at least 1 argument but got 0 (usage: `{fn_name}(seed, data...)`)"
+        ));
+    };
+    seed.set_type_coercion(SqlType::Int);
+    Ok(())
+}
+
+/// Get the seed for this row
+fn seed_arg(args: &ArgList<Process>) -> Option<u64> {
+    args.get(0).unwrap().value().as_int().map(|s| s as u64)
+}
+
+/// xxhash32 with a seed; only the low 32 bits of the seed are used
+struct XxHash32Seed;
+
+#[register(name = "xxhash32_seed")]
+impl BasicUdf for XxHash32Seed {
+    type Returns<'a> = Option<i64>;
+
+    fn init(_cfg: &UdfCfg<Init>, args: &ArgList<Init>) -> Result<Self, String> {
+        seed_init(args, "xxhash32_seed").map(|_| Self)
+    }
+
+    fn process<'a>(
+        &'a mut self,
+        _cfg: &UdfCfg<Process>,
+        args: &ArgList<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<Self::Returns<'a>, ProcessError> {
+        let Some(seed) = seed_arg(args) else {
+            return Ok(None);
+        };
+        let mut hasher = Xxh32::new(seed as u32);
+        args.iter()
+            .skip(1)
+            .for_each(|arg| hash_arg(arg, |buf| hasher.update(buf)));
+        Ok(Some(hasher.digest().into()))
+    }
+}
+
+/// xxhash64 with a seed
+struct XxHash64Seed;
+
+#[register(name = "xxhash64_seed", alias = "xxhash_seed")]
+impl BasicUdf for XxHash64Seed {
+    type Returns<'a> = Option<i64>;
+
+    fn init(_cfg: &UdfCfg<Init>, args: &ArgList<Init>) -> Result<Self, String> {
+        seed_init(args, "xxhash64_seed").map(|_| Self)
+    }
+
+    fn process<'a>(
+        &'a mut self,
+        _cfg: &UdfCfg<Process>,
+        args: &ArgList<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<Self::Returns<'a>, ProcessError> {
+        let Some(seed) = seed_arg(args) else {
+            return Ok(None);
+        };
+        let mut hasher = Xxh64::new(seed);
+        args.iter()
+            .skip(1)
+            .for_each(|arg| hash_arg(arg, |buf| hasher.update(buf)));
+        Ok(Some(hasher.digest() as i64))
+    }
+}
+
+/// xxhash3 with a seed
+struct XxHash3Seed;
+
+#[register(name = "xxhash3_seed")]
+impl BasicUdf for XxHash3Seed {
+    type Returns<'a> = Option<i64>;
+
+    fn init(_cfg: &UdfCfg<Init>, args: &ArgList<Init>) -> Result<Self, String> {
+        seed_init(args, "xxhash3_seed").map(|_| Self)
+    }
+
+    fn process<'a>(
+        &'a mut self,
+        _cfg: &UdfCfg<Process>,
+        args: &ArgList<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<Self::Returns<'a>, ProcessError> {
+        let Some(seed) = seed_arg(args) else {
+            return Ok(None);
+        };
+        let mut hasher = Xxh3::with_seed(seed);
+        args.iter()
+            .skip(1)
+            .for_each(|arg| hash_arg(arg, |buf| hasher.update(buf)));
+        Ok(Some(hasher.digest() as i64))
+    }
+}
+
+/// xxhash3 with a custom secret of at least 136 bytes. The secret should look
+/// random, e.g. `random_bytes(192)` or the output of `blake3_xof`.
+struct XxHash3Secret {
+    /// Arguments are collected here since the streaming hasher only accepts
+    /// secrets of one specific size
+    buf: Vec<u8>,
+}
+
+#[register(name = "xxhash3_secret")]
+impl BasicUdf for XxHash3Secret {
+    type Returns<'a> = Option<i64>;
+
+    fn init(_cfg: &UdfCfg<Init>, args: &ArgList<Init>) -> Result<Self, String> {
+        let Some(mut secret) = args.get(0) else {
+            return Err("xxhash3_secret takes at least 1 argument but got 0 \
+                (usage: `xxhash3_secret(secret, data...)`)"
+                .to_owned());
+        };
+
+        // Values must be checked before setting coercion
+        if let Some(s) = secret.value().as_bytes() {
+            if s.len() < MIN_SECRET_LEN {
+                return Err(format!(
+                    "xxhash3_secret requires a secret of at least {MIN_SECRET_LEN} bytes but got {}",
+                    s.len()
+                ));
+            }
+        }
+        secret.set_type_coercion(SqlType::String);
+
+        Ok(Self { buf: Vec::new() })
+    }
+
+    fn process<'a>(
+        &'a mut self,
+        _cfg: &UdfCfg<Process>,
+        args: &ArgList<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<Self::Returns<'a>, ProcessError> {
+        let secret_arg = args.get(0).unwrap().value();
+        let Some(secret) = secret_arg.as_bytes().and_then(SecretInput::try_new) else {
+            return Ok(None);
+        };
+
+        self.buf.clear();
+        args.iter()
+            .skip(1)
+            .for_each(|arg| hash_arg(arg, |buf| self.buf.extend_from_slice(buf)));
+        Ok(Some(xxh3_64_with_secret_input(&self.buf, &secret) as i64))
+    }
+}
+
+/// 128-bit xxhash3 as hex
+struct XxHash128 {
+    hasher: Xxh3,
+    hex: [u8; 32],
+}
+
+#[register(name = "xxhash128")]
+impl BasicUdf for XxHash128 {
+    type Returns<'a> = &'a [u8];
+
+    fn init(_cfg: &UdfCfg<Init>, _args: &ArgList<Init>) -> Result<Self, String> {
+        Ok(Self {
+            hasher: Xxh3::new(),
+            hex: [0u8; 32],
+        })
+    }
+
+    fn process<'a>(
+        &'a mut self,
+        _cfg: &UdfCfg<Process>,
+        args: &ArgList<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<Self::Returns<'a>, ProcessError> {
+        args.iter()
+            .for_each(|arg| hash_arg(arg, |buf| self.hasher.update(buf)));
+        let hashed = self.hasher.digest128().to_be_bytes();
+        self.hasher.reset();
+        data_encoding::HEXUPPER.encode_mut(&hashed, &mut self.hex);
+        Ok(&self.hex)
+    }
+}
+
+/// 128-bit xxhash3 as a big endian binary string
+struct XxHash128Bin {
+    hasher: Xxh3,
+    ret: [u8; 16],
+}
+
+#[register(name = "xxhash128_bin")]
+impl BasicUdf for XxHash128Bin {
+    type Returns<'a> = &'a [u8];
+
+    fn init(_cfg: &UdfCfg<Init>, _args: &ArgList<Init>) -> Result<Self, String> {
+        Ok(Self {
+            hasher: Xxh3::new(),
+            ret: [0u8; 16],
+        })
+    }
+
+    fn process<'a>(
+        &'a mut self,
+        _cfg: &UdfCfg<Process>,
+        args: &ArgList<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<Self::Returns<'a>, ProcessError> {
+        args.iter()
+            .for_each(|arg| hash_arg(arg, |buf| self.hasher.update(buf)));
+        self.ret = self.hasher.digest128().to_be_bytes();
+        self.hasher.reset();
+        Ok(&self.ret)
+    }
+}
+
+/// Longest decimal representation of a `u64`
+const U64_DECIMAL_LEN: u64 = 20;
+
+/// xxhash3 as an unsigned integer
+struct XxHash3U {
+    ret: String,
+}
+
+#[register(name = "xxhash3_u")]
+impl BasicUdf for XxHash3U {
+    type Returns<'a> = &'a str;
+
+    fn init(cfg: &UdfCfg<Init>, _args: &ArgList<Init>) -> Result<Self, String> {
+        cfg.set_max_len(U64_DECIMAL_LEN);
+        Ok(Self { ret: String::new() })
+    }
+
+    fn process<'a>(
+        &'a mut self,
+        _cfg: &UdfCfg<Process>,
+        args: &ArgList<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<Self::Returns<'a>, ProcessError> {
+        let hash = if args.len() == 1 {
+            hash_arg(args.get(0).unwrap(), xxh3_64)
+        } else {
+            let mut hasher = Xxh3::new();
+            args.iter()
+                .for_each(|arg| hash_arg(arg, |buf| hasher.update(buf)));
+            hasher.digest()
+        };
+        self.ret.clear();
+        write!(self.ret, "{hash}").unwrap();
+        Ok(&self.ret)
+    }
+}
+
+/// xxhash64 as an unsigned integer
+struct XxHash64U {
+    ret: String,
+}
+
+#[register(name = "xxhash64_u", alias = "xxhash_u")]
+impl BasicUdf for XxHash64U {
+    type Returns<'a> = &'a str;
+
+    fn init(cfg: &UdfCfg<Init>, _args: &ArgList<Init>) -> Result<Self, String> {
+        cfg.set_max_len(U64_DECIMAL_LEN);
+        Ok(Self { ret: String::new() })
+    }
+
+    fn process<'a>(
+        &'a mut self,
+        _cfg: &UdfCfg<Process>,
+        args: &ArgList<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<Self::Returns<'a>, ProcessError> {
+        let hash = if args.len() == 1 {
+            hash_arg(args.get(0).unwrap(), |buf| xxh64(buf, 0))
+        } else {
+            let mut hasher = Xxh64::new(0);
+            args.iter()
+                .for_each(|arg| hash_arg(arg, |buf| hasher.update(buf)));
+            hasher.digest()
+        };
+        self.ret.clear();
+        write!(self.ret, "{hash}").unwrap();
+        Ok(&self.ret)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use udf::mock::*;
+    use xxhash_rust::xxh3::{xxh3_128, xxh3_64_with_secret, xxh3_64_with_seed};
+
+    use super::*;
+
+    #[test]
+    fn test_seeded() {
+        let mut cfg = MockUdfCfg::new();
+        let mut args = mock_args![(Int 42, "", false), ("Hello, world!", "", false)];
+
+        let mut udf = XxHash64Seed::init(cfg.as_init(), args.as_init()).unwrap();
+        let res = udf
+            .process(cfg.as_process(), args.as_process(), None)
+            .unwrap();
+        assert_eq!(res, Some(xxh64(b"Hello, world!", 42) as i64));
+
+        let mut udf = XxHash3Seed::init(cfg.as_init(), args.as_init()).unwrap();
+        let res = udf
+            .process(cfg.as_process(), args.as_process(), None)
+            .unwrap();
+        assert_eq!(res, Some(xxh3_64_with_seed(b"Hello, world!", 42) as i64));
+
+        let mut args = mock_args![(Int None, "", false), ("Hello, world!", "", false)];
+        let res = udf
+            .process(cfg.as_process(), args.as_process(), None)
+            .unwrap();
+        assert_eq!(res, None);
+    }
+
+    #[test]
+    fn test_secret() {
+        let secret = "0123456789abcdef".repeat(12);
+        let mut cfg = MockUdfCfg::new();
+        let mut args = mock_args![
+            (secret.as_str(), "", false),
+            ("Hello, ", "", false),
+            ("world!", "", false)
+        ];
+
+        let mut udf = XxHash3Secret::init(cfg.as_init(), args.as_init()).unwrap();
+        let res = udf
+            .process(cfg.as_process(), args.as_process(), None)
+            .unwrap();
+        let expected = xxh3_64_with_secret(b"Hello, world!", secret.as_bytes());
+        assert_eq!(res, Some(expected as i64));
+
+        let mut args = mock_args![("too short", "", false), ("data", "", false)];
+        assert!(XxHash3Secret::init(cfg.as_init(), args.as_init()).is_err());
+    }
+
+    #[test]
+    fn test_xxhash128() {
+        let mut cfg = MockUdfCfg::new();
+        let mut args = mock_args![("Hello, ", "", false), ("world!", "", false)];
+
+        let mut udf = XxHash128Bin::init(cfg.as_init(), args.as_init()).unwrap();
+        let res = udf
+            .process(cfg.as_process(), args.as_process(), None)
+            .unwrap();
+        assert_eq!(res, xxh3_128(b"Hello, world!").to_be_bytes());
+    }
+
+    #[test]
+    fn test_unsigned() {
+        let mut cfg = MockUdfCfg::new();
+        let mut args = mock_args![("Hello, world!", "", false)];
+
+        let mut udf = XxHash64U::init(cfg.as_init(), args.as_init()).unwrap();
+        let res = udf
+            .process(cfg.as_process(), args.as_process(), None)
+            .unwrap();
+        assert_eq!(res, "17691043854468224118");
+
+        let mut args = mock_args![("Hello, ", "", false), ("world!", "", false)];
+        let mut udf = XxHash3U::init(cfg.as_init(), args.as_init()).unwrap();
+        let res = udf
+            .process(cfg.as_process(), args.as_process(), None)
+            .unwrap();
+        assert_eq!(res, "17564966470555134057");
+    }
+}
@@ -1,12 +1,44 @@
 //! UUID generators
 //!
 //! We store our results in our structs to avoid some allocations
+//!
+//! The name-based generators (`uuid_generate_v3` and `uuid_generate_v5`) accept the
+//! namespace either as a UUID string or as its 16-byte binary form, so both
+//! `uuid_ns_url()` and `uuid_to_bin(uuid_ns_url())` work. `uuid_v8_unpack` accepts
+//! its UUID the same way.
+//!
+//! `uuid_generate_v7_monotonic` follows RFC 9562 method 3: the 12 bits after the
+//! millisecond timestamp hold a sub-millisecond fraction. The last UUID is shared by
+//! every thread in the server, and if a new one would not sort after it, the last
+//! one plus one is used instead. Every UUID from this function is therefore
+//! strictly greater than the one before, even within one millisecond or if the
+//! clock moves backwards.
+//!
+//! `uuid_generate_v1`, `uuid_generate_v6`, and `uuid_generate_v7` can take a time
+//! to embed instead of the current time, as a `DATETIME` (in UTC) or Unix seconds.
+//! `uuid_v7_lower_bound` and `uuid_v7_upper_bound` give the smallest and largest v7
+//! UUIDs for the millisecond containing a time.
+//!
+//! v8 UUIDs hold 122 bits of custom data around the version and variant bits. The
+//! structured form splits them into a 60-bit `high` and a 62-bit `low` integer,
+//! following the RFC 9562 layout:
+//!
+//! ```text
+//! | high[59:12] (48) | version (4) | high[11:0] (12) | variant (2) | low (62) |
+//! ```
+
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use mac_address::get_mac_address;
 use udf::prelude::*;
 use uuid::Uuid;
 
-use crate::{validate_arg_count, HYPHENATED_UUID_LEN, HYPHENATED_UUID_LEN_U64};
+use crate::time::{timestamp_arg, timestamp_init, GREGORIAN_OFFSET};
+use crate::{
+    parse_uuid_arg, validate_arg_count, HYPHENATED_UUID_LEN, HYPHENATED_UUID_LEN_U64,
+    UUID_BYTES_LEN, UUID_BYTES_LEN_U64,
+};
 
 #[derive(Debug)]
 struct UuidGenerateV1 {
@@ -18,10 +50,18 @@ struct UuidGenerateV1 {
 
 #[register(name = "uuid_generate_v1")]
 impl BasicUdf for UuidGenerateV1 {
-    type Returns<'a> = &'a [u8];
+    type Returns<'a> = Option<&'a [u8]>;
 
     fn init(cfg: &UdfCfg<Init>, args: &ArgList<Init>) -> Result<Self, String> {
-        validate_arg_count(args.len(), 0, "uuid_generate_v1")?;
+        if args.len() > 1 {
+            return Err(format!(
+                "uuid_generate_v1 takes 0 or 1 arguments but got {} (usage: `uuid_generate_v1()` or `uuid_generate_v1(timestamp)`)",
+                args.len()
+            ));
+        }
+        if let Some(arg) = args.get(0) {
+            timestamp_init(&arg, "uuid_generate_v1")?;
+        }
         cfg.set_max_len(HYPHENATED_UUID_LEN_U64);
 
         // Try to get the mac address; just return zeroes if there are any issues
@@ -38,14 +78,21 @@ impl BasicUdf for UuidGenerateV1 {
     fn process<'a>(
         &'a mut self,
         _cfg: &UdfCfg<Process>,
-        _args: &ArgList<Process>,
+        args: &ArgList<Process>,
         _error: Option<NonZeroU8>,
     ) -> Result<Self::Returns<'a>, ProcessError> {
-        Uuid::now_v1(&self.mac)
-            .hyphenated()
-            .encode_lower(&mut self.res);
+        let uuid = match args.get(0) {
+            Some(arg) => {
+                let Some(ticks) = timestamp_arg(&arg.value()).and_then(gregorian_ticks) else {
+                    return Ok(None);
+                };
+                time_based_uuid(1, ticks, &self.mac)
+            }
+            None => Uuid::now_v1(&self.mac),
+        };
 
-        Ok(&self.res)
+        uuid.hyphenated().encode_lower(&mut self.res);
+        Ok(Some(&self.res))
     }
 }
 
@@ -107,24 +154,44 @@ impl BasicUdf for UuidGenerateV4 {
 
 /// V6 UUID, rearranged V1
 ///
-/// Allows specifying the mac address if desired
+/// Allows specifying the mac address and time if desired
 #[derive(Debug)]
 struct UuidGenerateV6([u8; HYPHENATED_UUID_LEN]);
 
+/// Whether the single argument of `uuid_generate_v6` is a node ID rather than a
+/// time. Only a 6-byte string is a node ID.
+fn is_node_id(value: &SqlResult) -> bool {
+    matches!(value, SqlResult::String(Some(bytes)) if bytes.len() == 6)
+}
+
 #[register(name = "uuid_generate_v6")]
 impl BasicUdf for UuidGenerateV6 {
-    type Returns<'a> = &'a [u8];
+    type Returns<'a> = Option<&'a [u8]>;
 
     fn init(cfg: &UdfCfg<Init>, args: &ArgList<Init>) -> Result<Self, String> {
-        if args.len() > 1 {
+        if args.len() > 2 {
             return Err(format!(
-                "uuid_generate_v6 takes 0 or 1 arguments but got {} (usage: `uuid_generate_v6()` or `uuid_generate_v6(node_id)`)",
+                "uuid_generate_v6 takes 0 to 2 arguments but got {} (usage: `uuid_generate_v6()`, `uuid_generate_v6(node_id)`, `uuid_generate_v6(timestamp)`, or `uuid_generate_v6(timestamp, node_id)`)",
                 args.len()
             ));
         }
 
-        if let Some(mut arg) = args.get(0) {
-            arg.set_type_coercion(SqlType::String);
+        match args.len() {
+            1 => {
+                let arg = args.get(0).unwrap();
+                if !is_node_id(&arg.value()) {
+                    timestamp_init(&arg, "uuid_generate_v6").map_err(|_| {
+                        "uuid_generate_v6: argument must be a 6-byte node ID, or a DATETIME \
+                        or Unix time in seconds"
+                            .to_owned()
+                    })?;
+                }
+            }
+            2 => {
+                timestamp_init(&args.get(0).unwrap(), "uuid_generate_v6")?;
+                args.get(1).unwrap().set_type_coercion(SqlType::String);
+            }
+            _ => (),
         }
 
         cfg.set_max_len(HYPHENATED_UUID_LEN_U64);
@@ -137,35 +204,396 @@ impl BasicUdf for UuidGenerateV6 {
         args: &ArgList<Process>,
         _error: Option<NonZeroU8>,
     ) -> Result<Self::Returns<'a>, ProcessError> {
-        let uuid = if let Some(arg) = args.get(0) {
-            let tmp = arg.value();
-            let bytes = tmp.as_bytes().unwrap();
-            let Ok(node_id) = bytes.try_into() else {
-                udf_log!(Warning: "uuid_generate_v6 expected argument of length 6; got {}", bytes.len());
-                return Err(ProcessError);
-            };
+        // A single argument is either the node ID or the time
+        let (ts_arg, node_arg) = match args.len() {
+            2 => (args.get(0), args.get(1)),
+            1 if is_node_id(&args.get(0).unwrap().value()) => (None, args.get(0)),
+            1 => (args.get(0), None),
+            _ => (None, None),
+        };
+
+        let node_value = node_arg.map(|arg| arg.value());
+        let node_id: [u8; 6] = match node_value.as_ref().and_then(SqlResult::as_bytes) {
+            Some(bytes) => {
+                let Ok(node_id) = bytes.try_into() else {
+                    udf_log!(Warning: "uuid_generate_v6 expected argument of length 6; got {}", bytes.len());
+                    return Err(ProcessError);
+                };
+                node_id
+            }
+            // A null or missing node is random
+            None => rand::random(),
+        };
 
-            Uuid::now_v6(node_id)
+        let uuid = if let Some(arg) = ts_arg {
+            let Some(ticks) = timestamp_arg(&arg.value()).and_then(gregorian_ticks) else {
+                return Ok(None);
+            };
+            time_based_uuid(6, ticks, &node_id)
         } else {
-            let rand_node: [u8; 6] = rand::random();
-            Uuid::now_v6(&rand_node)
+            Uuid::now_v6(&node_id)
         };
 
         uuid.hyphenated().encode_lower(&mut self.0);
-        Ok(&self.0)
+        Ok(Some(&self.0))
     }
 }
 
-/// V7 UUID: random UUID that starts with the current UNIX timestamp
+/// V7 UUID: random UUID that starts with the current UNIX timestamp, or a given one
 #[derive(Debug)]
 struct UuidGenerateV7([u8; HYPHENATED_UUID_LEN]);
 
 #[register(name = "uuid_generate_v7")]
 impl BasicUdf for UuidGenerateV7 {
+    type Returns<'a> = Option<&'a [u8]>;
+
+    fn init(cfg: &UdfCfg<Init>, args: &ArgList<Init>) -> Result<Self, String> {
+        if args.len() > 1 {
+            return Err(format!(
+                "uuid_generate_v7 takes 0 or 1 arguments but got {} (usage: `uuid_generate_v7()` or `uuid_generate_v7(timestamp)`)",
+                args.len()
+            ));
+        }
+        if let Some(arg) = args.get(0) {
+            timestamp_init(&arg, "uuid_generate_v7")?;
+        }
+        cfg.set_max_len(HYPHENATED_UUID_LEN_U64);
+        Ok(Self([0; HYPHENATED_UUID_LEN]))
+    }
+
+    fn process<'a>(
+        &'a mut self,
+        _cfg: &UdfCfg<Process>,
+        args: &ArgList<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<Self::Returns<'a>, ProcessError> {
+        let uuid = match args.get(0) {
+            Some(arg) => {
+                let Some(since_epoch) = timestamp_arg(&arg.value()).and_then(v7_duration) else {
+                    return Ok(None);
+                };
+                v7_from_bits(v7_bits_for(since_epoch))
+            }
+            None => Uuid::now_v7(),
+        };
+
+        uuid.hyphenated().encode_lower(&mut self.0);
+        Ok(Some(&self.0))
+    }
+}
+
+/// Smallest or largest v7 UUID for a time
+#[derive(Debug)]
+struct V7Bound {
+    upper: bool,
+    res: [u8; HYPHENATED_UUID_LEN],
+}
+
+impl V7Bound {
+    fn init(
+        cfg: &UdfCfg<Init>,
+        args: &ArgList<Init>,
+        fn_name: &str,
+        upper: bool,
+    ) -> Result<Self, String> {
+        validate_arg_count(args.len(), 1, fn_name)?;
+        timestamp_init(&args.get(0).unwrap(), fn_name)?;
+        cfg.set_max_len(HYPHENATED_UUID_LEN_U64);
+        Ok(Self {
+            upper,
+            res: [0; HYPHENATED_UUID_LEN],
+        })
+    }
+
+    fn process(&mut self, args: &ArgList<Process>) -> Option<&[u8]> {
+        let since_epoch = timestamp_arg(&args.get(0).unwrap().value()).and_then(v7_duration)?;
+        let ms = since_epoch.as_millis();
+        let rand_bits = if self.upper {
+            (1 << V7_RAND_BITS) - 1
+        } else {
+            0
+        };
+        v7_from_bits(ms << V7_RAND_BITS | rand_bits)
+            .hyphenated()
+            .encode_lower(&mut self.res);
+        Some(&self.res)
+    }
+}
+
+/// The smallest v7 UUID in the millisecond of a time
+#[derive(Debug)]
+struct UuidV7LowerBound(V7Bound);
+
+#[register(name = "uuid_v7_lower_bound")]
+impl BasicUdf for UuidV7LowerBound {
+    type Returns<'a> = Option<&'a [u8]>;
+
+    fn init(cfg: &UdfCfg<Init>, args: &ArgList<Init>) -> Result<Self, String> {
+        V7Bound::init(cfg, args, "uuid_v7_lower_bound", false).map(Self)
+    }
+
+    fn process<'a>(
+        &'a mut self,
+        _cfg: &UdfCfg<Process>,
+        args: &ArgList<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<Self::Returns<'a>, ProcessError> {
+        Ok(self.0.process(args))
+    }
+}
+
+/// The largest v7 UUID in the millisecond of a time
+#[derive(Debug)]
+struct UuidV7UpperBound(V7Bound);
+
+#[register(name = "uuid_v7_upper_bound")]
+impl BasicUdf for UuidV7UpperBound {
+    type Returns<'a> = Option<&'a [u8]>;
+
+    fn init(cfg: &UdfCfg<Init>, args: &ArgList<Init>) -> Result<Self, String> {
+        V7Bound::init(cfg, args, "uuid_v7_upper_bound", true).map(Self)
+    }
+
+    fn process<'a>(
+        &'a mut self,
+        _cfg: &UdfCfg<Process>,
+        args: &ArgList<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<Self::Returns<'a>, ProcessError> {
+        Ok(self.0.process(args))
+    }
+}
+
+/// 100ns ticks since the Gregorian epoch, `None` if the time is before 1582 or
+/// does not fit in 60 bits
+fn gregorian_ticks(micros: i64) -> Option<u64> {
+    let ticks = micros.checked_mul(10)?.checked_add(GREGORIAN_OFFSET)?;
+    (0..1 << 60).contains(&ticks).then_some(ticks as u64)
+}
+
+/// Create a v1 or v6 UUID from a timestamp and node, with a random clock sequence
+fn time_based_uuid(version: u8, ticks: u64, node: &[u8; 6]) -> Uuid {
+    let time_field = match version {
+        // time_low, time_mid, time_high
+        1 => (ticks & 0xffff_ffff) << 32 | (ticks >> 32 & 0xffff) << 16 | (ticks >> 48),
+        // time_high, time_mid, time_low
+        _ => (ticks >> 12) << 16 | (ticks & 0xfff),
+    };
+    let time_field = time_field & !(0xf << 12) | u64::from(version) << 12;
+    let clock_seq = rand::random::<u16>() & 0x3fff | 0x8000;
+
+    let mut bytes = [0u8; UUID_BYTES_LEN];
+    bytes[..8].copy_from_slice(&time_field.to_be_bytes());
+    bytes[8..10].copy_from_slice(&clock_seq.to_be_bytes());
+    bytes[10..].copy_from_slice(node);
+    Uuid::from_bytes(bytes)
+}
+
+/// Time since the Unix epoch for a v7 UUID, `None` if the time does not fit
+fn v7_duration(micros: i64) -> Option<std::time::Duration> {
+    let micros = u64::try_from(micros).ok()?;
+    (micros / 1000 < 1 << 48).then(|| std::time::Duration::from_micros(micros))
+}
+
+/// Shared `init` for v3 and v5. A constant namespace is checked here.
+fn name_based_init(
+    cfg: &UdfCfg<Init>,
+    args: &ArgList<Init>,
+    fn_name: &str,
+    max_len: u64,
+) -> Result<(), String> {
+    if args.len() != 2 {
+        return Err(format!(
+            "{fn_name} takes 2 arguments but got {} (usage: `{fn_name}(namespace, name)`)",
+            args.len()
+        ));
+    }
+
+    let mut namespace = args.get(0).unwrap();
+    if let Some(ns) = namespace.value().as_bytes() {
+        if parse_uuid_arg(ns).is_none() {
+            return Err(format!(
+                "{fn_name}: namespace must be a UUID or 16 bytes, got '{}'",
+                String::from_utf8_lossy(ns)
+            ));
+        }
+    }
+    namespace.set_type_coercion(SqlType::String);
+    args.get(1).unwrap().set_type_coercion(SqlType::String);
+    cfg.set_max_len(max_len);
+
+    Ok(())
+}
+
+/// Create a v3 or v5 UUID, `None` if either argument is null or the namespace is
+/// invalid
+fn name_based_uuid(args: &ArgList<Process>, new: fn(&Uuid, &[u8]) -> Uuid) -> Option<Uuid> {
+    let namespace = args.get(0).unwrap().value();
+    let name = args.get(1).unwrap().value();
+    let namespace = parse_uuid_arg(namespace.as_bytes()?)?;
+
+    Some(new(&namespace, name.as_bytes()?))
+}
+
+/// V3 UUID: MD5 hash of a namespace and name
+#[derive(Debug)]
+struct UuidGenerateV3([u8; HYPHENATED_UUID_LEN]);
+
+#[register(name = "uuid_generate_v3")]
+impl BasicUdf for UuidGenerateV3 {
+    type Returns<'a> = Option<&'a [u8]>;
+
+    fn init(cfg: &UdfCfg<Init>, args: &ArgList<Init>) -> Result<Self, String> {
+        name_based_init(cfg, args, "uuid_generate_v3", HYPHENATED_UUID_LEN_U64)?;
+        Ok(Self([0; HYPHENATED_UUID_LEN]))
+    }
+
+    fn process<'a>(
+        &'a mut self,
+        _cfg: &UdfCfg<Process>,
+        args: &ArgList<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<Self::Returns<'a>, ProcessError> {
+        let Some(uuid) = name_based_uuid(args, Uuid::new_v3) else {
+            return Ok(None);
+        };
+        uuid.hyphenated().encode_lower(&mut self.0);
+        Ok(Some(&self.0))
+    }
+}
+
+/// V3 UUID as 16 bytes
+#[derive(Debug)]
+struct UuidGenerateV3Bin([u8; UUID_BYTES_LEN]);
+
+#[register(name = "uuid_generate_v3_bin")]
+impl BasicUdf for UuidGenerateV3Bin {
+    type Returns<'a> = Option<&'a [u8]>;
+
+    fn init(cfg: &UdfCfg<Init>, args: &ArgList<Init>) -> Result<Self, String> {
+        name_based_init(cfg, args, "uuid_generate_v3_bin", UUID_BYTES_LEN_U64)?;
+        Ok(Self([0; UUID_BYTES_LEN]))
+    }
+
+    fn process<'a>(
+        &'a mut self,
+        _cfg: &UdfCfg<Process>,
+        args: &ArgList<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<Self::Returns<'a>, ProcessError> {
+        let Some(uuid) = name_based_uuid(args, Uuid::new_v3) else {
+            return Ok(None);
+        };
+        self.0 = uuid.into_bytes();
+        Ok(Some(&self.0))
+    }
+}
+
+/// V5 UUID: SHA1 hash of a namespace and name
+#[derive(Debug)]
+struct UuidGenerateV5([u8; HYPHENATED_UUID_LEN]);
+
+#[register(name = "uuid_generate_v5")]
+impl BasicUdf for UuidGenerateV5 {
+    type Returns<'a> = Option<&'a [u8]>;
+
+    fn init(cfg: &UdfCfg<Init>, args: &ArgList<Init>) -> Result<Self, String> {
+        name_based_init(cfg, args, "uuid_generate_v5", HYPHENATED_UUID_LEN_U64)?;
+        Ok(Self([0; HYPHENATED_UUID_LEN]))
+    }
+
+    fn process<'a>(
+        &'a mut self,
+        _cfg: &UdfCfg<Process>,
+        args: &ArgList<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<Self::Returns<'a>, ProcessError> {
+        let Some(uuid) = name_based_uuid(args, Uuid::new_v5) else {
+            return Ok(None);
+        };
+        uuid.hyphenated().encode_lower(&mut self.0);
+        Ok(Some(&self.0))
+    }
+}
+
+/// V5 UUID as 16 bytes
+#[derive(Debug)]
+struct UuidGenerateV5Bin([u8; UUID_BYTES_LEN]);
+
+#[register(name = "uuid_generate_v5_bin")]
+impl BasicUdf for UuidGenerateV5Bin {
+    type Returns<'a> = Option<&'a [u8]>;
+
+    fn init(cfg: &UdfCfg<Init>, args: &ArgList<Init>) -> Result<Self, String> {
+        name_based_init(cfg, args, "uuid_generate_v5_bin", UUID_BYTES_LEN_U64)?;
+        Ok(Self([0; UUID_BYTES_LEN]))
+    }
+
+    fn process<'a>(
+        &'a mut self,
+        _cfg: &UdfCfg<Process>,
+        args: &ArgList<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<Self::Returns<'a>, ProcessError> {
+        let Some(uuid) = name_based_uuid(args, Uuid::new_v5) else {
+            return Ok(None);
+        };
+        self.0 = uuid.into_bytes();
+        Ok(Some(&self.0))
+    }
+}
+
+/// Number of bits after the timestamp in a v7 UUID, not counting version and variant
+const V7_RAND_BITS: u32 = 74;
+/// The most recent `uuid_generate_v7_monotonic` value, without version and variant
+static LAST_V7: Mutex<u128> = Mutex::new(0);
+
+/// Create a v7 UUID from its 122 data bits: a 48-bit timestamp then 74 other bits
+fn v7_from_bits(bits: u128) -> Uuid {
+    let rand_a = (bits >> 62) & 0xfff;
+    let rand_b = bits & ((1 << 62) - 1);
+    let ts = bits >> V7_RAND_BITS;
+    let value = ts << 80 | 0x7 << 76 | rand_a << 64 | 0b10 << 62 | rand_b;
+    Uuid::from_u128(value)
+}
+
+/// Data bits for a time since the Unix epoch, with the sub-millisecond fraction in
+/// the 12 bits after the timestamp and the rest random
+fn v7_bits_for(since_epoch: std::time::Duration) -> u128 {
+    let ms = since_epoch.as_millis() & ((1 << 48) - 1);
+    let frac = u128::from(since_epoch.subsec_nanos() % 1_000_000) * 4096 / 1_000_000;
+    let rand_b = rand::random::<u64>() >> 2;
+    ms << V7_RAND_BITS | frac << 62 | u128::from(rand_b)
+}
+
+/// The next v7 UUID that is greater than any returned before
+fn next_monotonic_v7() -> Uuid {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    let candidate = v7_bits_for(now);
+
+    // A poisoned lock still holds a valid value
+    let mut last = LAST_V7.lock().unwrap_or_else(|e| e.into_inner());
+    let next = if candidate > *last {
+        candidate
+    } else {
+        *last + 1
+    };
+    *last = next;
+    v7_from_bits(next)
+}
+
+/// V7 UUID that always sorts after the previous one
+#[derive(Debug)]
+struct UuidGenerateV7Monotonic([u8; HYPHENATED_UUID_LEN]);
+
+#[register(name = "uuid_generate_v7_monotonic")]
+impl BasicUdf for UuidGenerateV7Monotonic {
     type Returns<'a> = &'a [u8];
 
     fn init(cfg: &UdfCfg<Init>, args: &ArgList<Init>) -> Result<Self, String> {
-        validate_arg_count(args.len(), 0, "uuid_generate_v7")?;
+        validate_arg_count(args.len(), 0, "uuid_generate_v7_monotonic")?;
         cfg.set_max_len(HYPHENATED_UUID_LEN_U64);
         Ok(Self([0; HYPHENATED_UUID_LEN]))
     }
@@ -176,7 +604,412 @@ impl BasicUdf for UuidGenerateV7 {
         _args: &ArgList<Process>,
         _error: Option<NonZeroU8>,
     ) -> Result<Self::Returns<'a>, ProcessError> {
-        Uuid::now_v7().hyphenated().encode_lower(&mut self.0);
+        next_monotonic_v7().hyphenated().encode_lower(&mut self.0);
         Ok(&self.0)
     }
 }
+
+/// Largest `high` value for `uuid_v8_pack`
+const V8_HIGH_MAX: i64 = (1 << 60) - 1;
+/// Largest `low` value for `uuid_v8_pack`
+const V8_LOW_MAX: i64 = (1 << 62) - 1;
+
+/// Place `high` and `low` around the version and variant bits
+fn v8_pack(high: u64, low: u64) -> Uuid {
+    let high = ((high >> 12) << 16) | (0x8 << 12) | (high & 0xfff);
+    let low = (0b10 << 62) | low;
+    let mut bytes = [0u8; UUID_BYTES_LEN];
+    bytes[..8].copy_from_slice(&high.to_be_bytes());
+    bytes[8..].copy_from_slice(&low.to_be_bytes());
+    Uuid::from_bytes(bytes)
+}
+
+/// Inverse of `v8_pack`, `None` if this is not a v8 UUID
+fn v8_unpack(uuid: &Uuid) -> Option<(u64, u64)> {
+    if uuid.get_version_num() != 8 || uuid.get_variant() != uuid::Variant::RFC4122 {
+        return None;
+    }
+    let (high, low) = uuid.as_u64_pair();
+    let high = ((high >> 16) << 12) | (high & 0xfff);
+    Some((high, low & V8_LOW_MAX as u64))
+}
+
+/// V8 UUID from 16 bytes of custom data
+#[derive(Debug)]
+struct UuidGenerateV8([u8; HYPHENATED_UUID_LEN]);
+
+#[register(name = "uuid_generate_v8")]
+impl BasicUdf for UuidGenerateV8 {
+    type Returns<'a> = Option<&'a [u8]>;
+
+    fn init(cfg: &UdfCfg<Init>, args: &ArgList<Init>) -> Result<Self, String> {
+        validate_arg_count(args.len(), 1, "uuid_generate_v8")?;
+        let mut arg = args.get(0).unwrap();
+        if let Some(bytes) = arg.value().as_bytes() {
+            if bytes.len() != UUID_BYTES_LEN {
+                return Err(format!(
+                    "uuid_generate_v8 requires {UUID_BYTES_LEN} bytes but got {}",
+                    bytes.len()
+                ));
+            }
+        }
+        arg.set_type_coercion(SqlType::String);
+        cfg.set_max_len(HYPHENATED_UUID_LEN_U64);
+        Ok(Self([0; HYPHENATED_UUID_LEN]))
+    }
+
+    fn process<'a>(
+        &'a mut self,
+        _cfg: &UdfCfg<Process>,
+        args: &ArgList<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<Self::Returns<'a>, ProcessError> {
+        let input = args.get(0).unwrap().value();
+        let Some(Ok(bytes)) = input.as_bytes().map(<[u8; UUID_BYTES_LEN]>::try_from) else {
+            return Ok(None);
+        };
+        Uuid::new_v8(bytes).hyphenated().encode_lower(&mut self.0);
+        Ok(Some(&self.0))
+    }
+}
+
+/// V8 UUID from a 60-bit and a 62-bit integer
+#[derive(Debug)]
+struct UuidV8Pack([u8; HYPHENATED_UUID_LEN]);
+
+#[register(name = "uuid_v8_pack")]
+impl BasicUdf for UuidV8Pack {
+    type Returns<'a> = Option<&'a [u8]>;
+
+    fn init(cfg: &UdfCfg<Init>, args: &ArgList<Init>) -> Result<Self, String> {
+        if args.len() != 2 {
+            return Err(format!(
+                "uuid_v8_pack takes 2 arguments but got {} (usage: `uuid_v8_pack(high, low)`)",
+                args.len()
+            ));
+        }
+
+        for (idx, name, max) in [(0, "high", V8_HIGH_MAX), (1, "low", V8_LOW_MAX)] {
+            let mut arg = args.get(idx).unwrap();
+            // Values must be checked before setting coercion
+            if let Some(n) = arg.value().as_int() {
+                if !(0..=max).contains(&n) {
+                    return Err(format!(
+                        "uuid_v8_pack: {name} must be between 0 and {max}, got {n}"
+                    ));
+                }
+            }
+            arg.set_type_coercion(SqlType::Int);
+        }
+
+        cfg.set_max_len(HYPHENATED_UUID_LEN_U64);
+        Ok(Self([0; HYPHENATED_UUID_LEN]))
+    }
+
+    fn process<'a>(
+        &'a mut self,
+        _cfg: &UdfCfg<Process>,
+        args: &ArgList<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<Self::Returns<'a>, ProcessError> {
+        let high = args.get(0).unwrap().value().as_int();
+        let low = args.get(1).unwrap().value().as_int();
+        let (Some(high), Some(low)) = (high, low) else {
+            return Ok(None);
+        };
+        if !(0..=V8_HIGH_MAX).contains(&high) || !(0..=V8_LOW_MAX).contains(&low) {
+            return Ok(None);
+        }
+
+        v8_pack(high as u64, low as u64)
+            .hyphenated()
+            .encode_lower(&mut self.0);
+        Ok(Some(&self.0))
+    }
+}
+
+/// Which integer `uuid_v8_unpack` returns
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum V8Part {
+    High,
+    Low,
+}
+
+impl V8Part {
+    fn from_name(name: &[u8]) -> Option<Self> {
+        match name.to_ascii_lowercase().as_slice() {
+            b"high" => Some(Self::High),
+            b"low" => Some(Self::Low),
+            _ => None,
+        }
+    }
+}
+
+/// Get `high` or `low` back from a v8 UUID created by `uuid_v8_pack`
+#[derive(Debug)]
+struct UuidV8Unpack;
+
+#[register(name = "uuid_v8_unpack")]
+impl BasicUdf for UuidV8Unpack {
+    type Returns<'a> = Option<i64>;
+
+    fn init(_cfg: &UdfCfg<Init>, args: &ArgList<Init>) -> Result<Self, String> {
+        if args.len() != 2 {
+            return Err(format!(
+                "uuid_v8_unpack takes 2 arguments but got {} \
+                (usage: `uuid_v8_unpack(uuid, 'high')` or `uuid_v8_unpack(uuid, 'low')`)",
+                args.len()
+            ));
+        }
+
+        let mut part = args.get(1).unwrap();
+        if let Some(name) = part.value().as_bytes() {
+            if V8Part::from_name(name).is_none() {
+                return Err(format!(
+                    "uuid_v8_unpack: part must be 'high' or 'low', got '{}'",
+                    String::from_utf8_lossy(name)
+                ));
+            }
+        }
+        part.set_type_coercion(SqlType::String);
+        args.get(0).unwrap().set_type_coercion(SqlType::String);
+        Ok(Self)
+    }
+
+    fn process<'a>(
+        &'a mut self,
+        _cfg: &UdfCfg<Process>,
+        args: &ArgList<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<Self::Returns<'a>, ProcessError> {
+        let input = args.get(0).unwrap().value();
+        let part = args.get(1).unwrap().value();
+        let Some(part) = part.as_bytes().and_then(V8Part::from_name) else {
+            return Ok(None);
+        };
+        let Some((high, low)) = input
+            .as_bytes()
+            .and_then(parse_uuid_arg)
+            .and_then(|uuid| v8_unpack(&uuid))
+        else {
+            return Ok(None);
+        };
+
+        let ret = match part {
+            V8Part::High => high,
+            V8Part::Low => low,
+        };
+        Ok(Some(ret as i64))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use udf::mock::*;
+
+    use super::*;
+
+    #[test]
+    fn test_name_based() {
+        let mut cfg = MockUdfCfg::new();
+        let mut args = mock_args![
+            ("6ba7b810-9dad-11d1-80b4-00c04fd430c8", "", false),
+            ("www.example.com", "", false)
+        ];
+
+        let mut udf = UuidGenerateV3::init(cfg.as_init(), args.as_init()).unwrap();
+        let res = udf
+            .process(cfg.as_process(), args.as_process(), None)
+            .unwrap();
+        assert_eq!(res.unwrap(), b"5df41881-3aed-3515-88a7-2f4a814cf09e");
+
+        let mut udf = UuidGenerateV5::init(cfg.as_init(), args.as_init()).unwrap();
+        let res = udf
+            .process(cfg.as_process(), args.as_process(), None)
+            .unwrap();
+        assert_eq!(res.unwrap(), b"2ed6657d-e927-568b-95e1-2665a8aea6a2");
+
+        // 16 bytes are treated as a binary namespace
+        let ns = b"0123456789abcdef";
+        let mut args = mock_args![("0123456789abcdef", "", false), ("name", "", false)];
+        let mut udf = UuidGenerateV5Bin::init(cfg.as_init(), args.as_init()).unwrap();
+        let res = udf
+            .process(cfg.as_process(), args.as_process(), None)
+            .unwrap();
+        assert_eq!(
+            res.unwrap(),
+            Uuid::new_v5(&Uuid::from_bytes(*ns), b"name").as_bytes()
+        );
+    }
+
+    #[test]
+    fn test_name_based_invalid() {
+        let mut cfg = MockUdfCfg::new();
+        let mut args = mock_args![("not a uuid", "", false), ("x", "", false)];
+        assert_eq!(
+            UuidGenerateV3::init(cfg.as_init(), args.as_init()).unwrap_err(),
+            "uuid_generate_v3: namespace must be a UUID or 16 bytes, got 'not a uuid'"
+        );
+
+        let mut args = mock_args![("x", "", false)];
+        assert_eq!(
+            UuidGenerateV5::init(cfg.as_init(), args.as_init()).unwrap_err(),
+            "uuid_generate_v5 takes 2 arguments but got 1 \
+            (usage: `uuid_generate_v5(namespace, name)`)"
+        );
+    }
+
+    #[test]
+    fn test_v8_pack() {
+        let uuid = v8_pack(V8_HIGH_MAX as u64, 0);
+        assert_eq!(
+            uuid.hyphenated().to_string(),
+            "ffffffff-ffff-8fff-8000-000000000000"
+        );
+        let uuid = v8_pack(0, V8_LOW_MAX as u64);
+        assert_eq!(
+            uuid.hyphenated().to_string(),
+            "00000000-0000-8000-bfff-ffffffffffff"
+        );
+
+        for (high, low) in [
+            (0, 0),
+            (1, 2),
+            (0x123_4567_89ab_cdef, 0x3edc_ba98_7654_3210),
+        ] {
+            let uuid = v8_pack(high, low);
+            assert_eq!(uuid.get_version_num(), 8);
+            assert_eq!(v8_unpack(&uuid), Some((high, low)));
+        }
+
+        assert_eq!(v8_unpack(&Uuid::NAMESPACE_DNS), None);
+    }
+
+    #[test]
+    fn test_v8_pack_args() {
+        let mut cfg = MockUdfCfg::new();
+        let mut args = mock_args![(-1, "", false), (0, "", false)];
+        assert_eq!(
+            UuidV8Pack::init(cfg.as_init(), args.as_init()).unwrap_err(),
+            format!("uuid_v8_pack: high must be between 0 and {V8_HIGH_MAX}, got -1")
+        );
+
+        let mut args = mock_args![("abc", "", false), ("middle", "", false)];
+        assert_eq!(
+            UuidV8Unpack::init(cfg.as_init(), args.as_init()).unwrap_err(),
+            "uuid_v8_unpack: part must be 'high' or 'low', got 'middle'"
+        );
+    }
+
+    #[test]
+    fn test_v7_bits() {
+        let uuid = v7_from_bits(u128::MAX >> 6);
+        assert_eq!(
+            uuid.hyphenated().to_string(),
+            "ffffffff-ffff-7fff-bfff-ffffffffffff"
+        );
+
+        let bits = v7_bits_for(std::time::Duration::from_nanos(1_645_557_742_000_500_000));
+        let uuid = v7_from_bits(bits);
+        assert_eq!(uuid.get_version_num(), 7);
+        assert!(uuid
+            .hyphenated()
+            .to_string()
+            .starts_with("017f22e2-79b0-7800-"));
+    }
+
+    #[test]
+    fn test_v7_monotonic() {
+        let threads: Vec<_> = (0..4)
+            .map(|_| {
+                std::thread::spawn(|| (0..10_000).map(|_| next_monotonic_v7()).collect::<Vec<_>>())
+            })
+            .collect();
+
+        let mut all = Vec::new();
+        for thread in threads {
+            let uuids = thread.join().unwrap();
+            assert!(uuids.windows(2).all(|w| w[0] < w[1]));
+            assert!(uuids.iter().all(|u| u.get_version_num() == 7));
+            all.extend(uuids);
+        }
+
+        all.sort();
+        all.dedup();
+        assert_eq!(all.len(), 40_000);
+    }
+
+    #[test]
+    fn test_time_based() {
+        // Example from RFC 9562: 2022-02-22 19:22:22 UTC
+        let micros = 1_645_557_742_000_000;
+        let node = [0x9f, 0x6b, 0xde, 0xce, 0xd8, 0x46];
+        let ticks = gregorian_ticks(micros).unwrap();
+
+        let v1 = time_based_uuid(1, ticks, &node).hyphenated().to_string();
+        assert!(v1.starts_with("c232ab00-9414-11ec-"), "{v1}");
+        assert!(v1.ends_with("-9f6bdeced846"), "{v1}");
+        let v6 = time_based_uuid(6, ticks, &node).hyphenated().to_string();
+        assert!(v6.starts_with("1ec9414c-232a-6b00-"), "{v6}");
+
+        assert_eq!(gregorian_ticks(-12_219_292_800_000_001), None);
+        assert_eq!(v7_duration(-1), None);
+        assert_eq!(v7_duration(1 << 60), None);
+    }
+
+    #[test]
+    fn test_v7_bounds() {
+        let mut cfg = MockUdfCfg::new();
+        let mut args = mock_args![("2022-02-22 19:22:22", "", false)];
+        let mut lower = UuidV7LowerBound::init(cfg.as_init(), args.as_init()).unwrap();
+        let mut upper = UuidV7UpperBound::init(cfg.as_init(), args.as_init()).unwrap();
+
+        let res = lower
+            .process(cfg.as_process(), args.as_process(), None)
+            .unwrap();
+        assert_eq!(res.unwrap(), b"017f22e2-79b0-7000-8000-000000000000");
+        let res = upper
+            .process(cfg.as_process(), args.as_process(), None)
+            .unwrap();
+        assert_eq!(res.unwrap(), b"017f22e2-79b0-7fff-bfff-ffffffffffff");
+
+        let mut args = mock_args![("not a time", "", false)];
+        assert_eq!(
+            UuidV7LowerBound::init(cfg.as_init(), args.as_init()).unwrap_err(),
+            "uuid_v7_lower_bound: timestamp must be a DATETIME or Unix time in seconds"
+        );
+    }
+
+    #[test]
+    fn test_v6_args() {
+        let mut cfg = MockUdfCfg::new();
+        let run = |cfg: &mut MockUdfCfg, mut args: MockArgList| {
+            let mut udf = UuidGenerateV6::init(cfg.as_init(), args.as_init()).unwrap();
+            let res = udf
+                .process(cfg.as_process(), args.as_process(), None)
+                .unwrap();
+            String::from_utf8(res.unwrap().to_vec()).unwrap()
+        };
+
+        // A 6-byte string is the node ID
+        let res = run(&mut cfg, mock_args![("abcdef", "", false)]);
+        assert!(res.ends_with("-616263646566"), "{res}");
+        assert!(!res.starts_with("1ec9414c-"), "{res}");
+
+        // Anything else is the time, with a random node
+        let res = run(&mut cfg, mock_args![("2022-02-22 19:22:22", "", false)]);
+        assert!(res.starts_with("1ec9414c-232a-6b00-"), "{res}");
+        let res = run(&mut cfg, mock_args![(1_645_557_742, "", false)]);
+        assert!(res.starts_with("1ec9414c-232a-6b00-"), "{res}");
+
+        let res = run(
+            &mut cfg,
+            mock_args![("2022-02-22 19:22:22", "", false), ("abcdef", "", false)],
+        );
+        assert!(res.starts_with("1ec9414c-232a-6b00-"), "{res}");
+        assert!(res.ends_with("-616263646566"), "{res}");
+
+        let mut args = mock_args![("abc", "", false)];
+        assert!(UuidGenerateV6::init(cfg.as_init(), args.as_init()).is_err());
+    }
+}
@@ -0,0 +1,245 @@
+//! UUID introspection
+//!
+//! Each function takes a UUID as text or as the 16 bytes that `uuid_to_bin`
+//! produces, and returns `NULL` if it can't be parsed or doesn't contain the
+//! requested field. `uuid_to_bin(u, true)` output must be converted back first.
+
+use std::fmt::Write;
+
+use udf::prelude::*;
+use uuid::{Uuid, Variant};
+
+use crate::time::{write_datetime, DATETIME_LEN, GREGORIAN_OFFSET};
+use crate::{parse_uuid_arg, validate_arg_count};
+
+/// Parse the first argument of a row
+fn uuid_arg(args: &ArgList<Process>) -> Option<Uuid> {
+    parse_uuid_arg(args.get(0).unwrap().value().as_bytes()?)
+}
+
+/// Shared `init` for every function in this module
+fn inspect_init(args: &ArgList<Init>, fn_name: &str) -> Result<(), String> {
+    validate_arg_count(args.len(), 1, fn_name)?;
+    args.get(0).unwrap().set_type_coercion(SqlType::String);
+    Ok(())
+}
+
+/// The version of a UUID with the RFC 4122 / RFC 9562 variant
+fn rfc_version(uuid: &Uuid) -> Option<usize> {
+    (uuid.get_variant() == Variant::RFC4122).then(|| uuid.get_version_num())
+}
+
+/// Microseconds since the Unix epoch for v1, v6, and v7 UUIDs
+fn timestamp_micros(uuid: &Uuid) -> Option<i64> {
+    let b = uuid.as_bytes();
+    let be_u64 = |bytes: &[u8]| bytes.iter().fold(0u64, |acc, x| (acc << 8) | u64::from(*x));
+
+    let ticks = match rfc_version(uuid)? {
+        // time_low, time_mid, then time_high behind the version
+        1 => (be_u64(&b[6..8]) & 0x0fff) << 48 | be_u64(&b[4..6]) << 32 | be_u64(&b[0..4]),
+        // The same fields from most to least significant
+        6 => be_u64(&b[0..6]) << 12 | (be_u64(&b[6..8]) & 0x0fff),
+        7 => return Some(be_u64(&b[0..6]) as i64 * 1000),
+        _ => return None,
+    };
+
+    Some((ticks as i64 - GREGORIAN_OFFSET).div_euclid(10))
+}
+
+/// Node ID of a v1 or v6 UUID
+fn node_id(uuid: &Uuid) -> Option<[u8; 6]> {
+    matches!(rfc_version(uuid)?, 1 | 6).then(|| uuid.as_bytes()[10..].try_into().unwrap())
+}
+
+/// Clock sequence of a v1 or v6 UUID
+fn clock_seq(uuid: &Uuid) -> Option<u16> {
+    let b = uuid.as_bytes();
+    matches!(rfc_version(uuid)?, 1 | 6).then(|| u16::from_be_bytes([b[8], b[9]]) & 0x3fff)
+}
+
+/// Version number of any UUID (the value of the version bits)
+#[derive(Debug)]
+struct UuidVersion;
+
+#[register(name = "uuid_version")]
+impl BasicUdf for UuidVersion {
+    type Returns<'a> = Option<i64>;
+
+    fn init(_cfg: &UdfCfg<Init>, args: &ArgList<Init>) -> Result<Self, String> {
+        inspect_init(args, "uuid_version")?;
+        Ok(Self)
+    }
+
+    fn process<'a>(
+        &'a mut self,
+        _cfg: &UdfCfg<Process>,
+        args: &ArgList<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<Self::Returns<'a>, ProcessError> {
+        Ok(uuid_arg(args).map(|uuid| uuid.get_version_num() as i64))
+    }
+}
+
+/// Variant name: `NCS`, `RFC4122`, `Microsoft`, or `Future`
+#[derive(Debug)]
+struct UuidVariant;
+
+#[register(name = "uuid_variant")]
+impl BasicUdf for UuidVariant {
+    type Returns<'a> = Option<&'a str>;
+
+    fn init(_cfg: &UdfCfg<Init>, args: &ArgList<Init>) -> Result<Self, String> {
+        inspect_init(args, "uuid_variant")?;
+        Ok(Self)
+    }
+
+    fn process<'a>(
+        &'a mut self,
+        _cfg: &UdfCfg<Process>,
+        args: &ArgList<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<Self::Returns<'a>, ProcessError> {
+        let ret = uuid_arg(args).map(|uuid| match uuid.get_variant() {
+            Variant::NCS => "NCS",
+            Variant::RFC4122 => "RFC4122",
+            Variant::Microsoft => "Microsoft",
+            _ => "Future",
+        });
+        Ok(ret)
+    }
+}
+
+/// Timestamp of a v1, v6, or v7 UUID as a UTC `DATETIME(6)` string
+#[derive(Debug)]
+struct UuidTimestamp(String);
+
+#[register(name = "uuid_timestamp")]
+impl BasicUdf for UuidTimestamp {
+    type Returns<'a> = Option<&'a str>;
+
+    fn init(cfg: &UdfCfg<Init>, args: &ArgList<Init>) -> Result<Self, String> {
+        inspect_init(args, "uuid_timestamp")?;
+        cfg.set_max_len(DATETIME_LEN);
+        Ok(Self(String::with_capacity(DATETIME_LEN as usize)))
+    }
+
+    fn process<'a>(
+        &'a mut self,
+        _cfg: &UdfCfg<Process>,
+        args: &ArgList<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<Self::Returns<'a>, ProcessError> {
+        let Some(micros) = uuid_arg(args).as_ref().and_then(timestamp_micros) else {
+            return Ok(None);
+        };
+        write_datetime(&mut self.0, micros);
+        Ok(Some(&self.0))
+    }
+}
+
+/// Node ID (usually a MAC address) of a v1 or v6 UUID, as `xx:xx:xx:xx:xx:xx`
+#[derive(Debug)]
+struct UuidNode(String);
+
+#[register(name = "uuid_node")]
+impl BasicUdf for UuidNode {
+    type Returns<'a> = Option<&'a str>;
+
+    fn init(cfg: &UdfCfg<Init>, args: &ArgList<Init>) -> Result<Self, String> {
+        inspect_init(args, "uuid_node")?;
+        cfg.set_max_len(17);
+        Ok(Self(String::with_capacity(17)))
+    }
+
+    fn process<'a>(
+        &'a mut self,
+        _cfg: &UdfCfg<Process>,
+        args: &ArgList<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<Self::Returns<'a>, ProcessError> {
+        let Some(node) = uuid_arg(args).as_ref().and_then(node_id) else {
+            return Ok(None);
+        };
+        self.0.clear();
+        for (i, byte) in node.iter().enumerate() {
+            let sep = if i == 0 { "" } else { ":" };
+            write!(self.0, "{sep}{byte:02x}").unwrap();
+        }
+        Ok(Some(&self.0))
+    }
+}
+
+/// Clock sequence of a v1 or v6 UUID
+#[derive(Debug)]
+struct UuidClockSeq;
+
+#[register(name = "uuid_clock_seq")]
+impl BasicUdf for UuidClockSeq {
+    type Returns<'a> = Option<i64>;
+
+    fn init(_cfg: &UdfCfg<Init>, args: &ArgList<Init>) -> Result<Self, String> {
+        inspect_init(args, "uuid_clock_seq")?;
+        Ok(Self)
+    }
+
+    fn process<'a>(
+        &'a mut self,
+        _cfg: &UdfCfg<Process>,
+        args: &ArgList<Process>,
+        _error: Option<NonZeroU8>,
+    ) -> Result<Self::Returns<'a>, ProcessError> {
+        Ok(uuid_arg(args).as_ref().and_then(clock_seq).map(i64::from))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Example UUIDs from RFC 9562, all created at 2022-02-22 14:22:22 -05:00
+    const V1: Uuid = uuid::uuid!("c232ab00-9414-11ec-b3c8-9f6bdeced846");
+    const V6: Uuid = uuid::uuid!("1ec9414c-232a-6b00-b3c8-9f6bdeced846");
+    const V7: Uuid = uuid::uuid!("017f22e2-79b0-7cc3-98c4-dc0c0c07398f");
+
+    fn datetime(micros: i64) -> String {
+        let mut ret = String::new();
+        write_datetime(&mut ret, micros);
+        ret
+    }
+
+    #[test]
+    fn test_timestamp() {
+        assert_eq!(
+            datetime(timestamp_micros(&V1).unwrap()),
+            "2022-02-22 19:22:22.000000"
+        );
+        assert_eq!(timestamp_micros(&V1), timestamp_micros(&V6));
+        assert_eq!(
+            datetime(timestamp_micros(&V7).unwrap()),
+            "2022-02-22 19:22:22.000000"
+        );
+        assert_eq!(
+            timestamp_micros(&Uuid::NAMESPACE_DNS),
+            Some(886_630_433_151_182)
+        );
+        assert_eq!(timestamp_micros(&Uuid::nil()), None);
+        assert_eq!(datetime(-1), "1969-12-31 23:59:59.999999");
+
+        // The Gregorian epoch itself
+        let epoch = uuid::uuid!("00000000-0000-1000-8000-000000000000");
+        assert_eq!(
+            datetime(timestamp_micros(&epoch).unwrap()),
+            "1582-10-15 00:00:00.000000"
+        );
+    }
+
+    #[test]
+    fn test_node_clock_seq() {
+        let node = [0x9f, 0x6b, 0xde, 0xce, 0xd8, 0x46];
+        assert_eq!(node_id(&V1), Some(node));
+        assert_eq!(node_id(&V6), Some(node));
+        assert_eq!(node_id(&V7), None);
+        assert_eq!(clock_seq(&V1), Some(0x33c8));
+        assert_eq!(clock_seq(&V7), None);
+    }
+}
@@ -1,9 +1,12 @@
 pub mod convert;
 pub mod generate;
+pub mod inspect;
 pub mod namespaces;
+mod time;
 pub mod valid;
 
 use uuid::fmt::Hyphenated;
+use uuid::Uuid;
 
 const HYPHENATED_UUID_LEN: usize = Hyphenated::LENGTH;
 const HYPHENATED_UUID_LEN_U64: u64 = HYPHENATED_UUID_LEN as u64;
@@ -26,3 +29,12 @@ pub fn validate_arg_count(count: usize, expected: usize, fn_name: &str) -> Resul
         Ok(())
     }
 }
+
+/// Parse a UUID given either as text or as the 16 bytes from `uuid_to_bin`
+fn parse_uuid_arg(bytes: &[u8]) -> Option<Uuid> {
+    if bytes.len() == UUID_BYTES_LEN {
+        Uuid::from_slice(bytes).ok()
+    } else {
+        Uuid::try_parse_ascii(bytes).ok()
+    }
+}
@@ -0,0 +1,243 @@
+//! Conversions between UUID timestamps, Unix time, and `DATETIME` strings
+//!
+//! Times are microseconds since the Unix epoch, which matches `DATETIME(6)`. Dates
+//! use the proleptic Gregorian calendar in UTC, since a UDF can't see the session
+//! time zone.
+
+use std::fmt::Write;
+
+use udf::prelude::*;
+
+/// 100ns intervals between the Gregorian epoch (1582-10-15) and the Unix epoch
+pub(crate) const GREGORIAN_OFFSET: i64 = 0x01B2_1DD2_1381_4000;
+/// Length of `YYYY-MM-DD HH:MM:SS.ffffff`
+pub(crate) const DATETIME_LEN: u64 = 26;
+const MICROS_PER_SEC: i64 = 1_000_000;
+const MICROS_PER_DAY: i64 = 86_400 * MICROS_PER_SEC;
+
+/// Convert days since the Unix epoch to a `(year, month, day)` date. This is
+/// Howard Hinnant's `civil_from_days`, valid for any day in range.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month as u32, day as u32)
+}
+
+/// Inverse of `civil_from_days`
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = year - i64::from(month <= 2);
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = i64::from((month + 9) % 12);
+    let doy = (153 * mp + 2) / 5 + i64::from(day) - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// Write `YYYY-MM-DD HH:MM:SS.ffffff` in UTC
+pub(crate) fn write_datetime(out: &mut String, micros: i64) {
+    let (year, month, day) = civil_from_days(micros.div_euclid(MICROS_PER_DAY));
+    let time = micros.rem_euclid(MICROS_PER_DAY);
+    let secs = time / MICROS_PER_SEC;
+    out.clear();
+    write!(
+        out,
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02}.{:06}",
+        secs / 3600,
+        secs / 60 % 60,
+        secs % 60,
+        time % MICROS_PER_SEC
+    )
+    .unwrap();
+}
+
+/// Parse a decimal number of seconds exactly, with up to 6 fractional digits
+fn parse_decimal_secs(s: &str) -> Option<i64> {
+    let s = s.trim();
+    let (negative, digits) = match s.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, s.strip_prefix('+').unwrap_or(s)),
+    };
+    let (int, frac) = digits.split_once('.').unwrap_or((digits, ""));
+    if int.is_empty() && frac.is_empty() || frac.len() > 6 {
+        return None;
+    }
+    if !int.bytes().chain(frac.bytes()).all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+
+    let int: i64 = if int.is_empty() { 0 } else { int.parse().ok()? };
+    let frac: i64 = format!("{frac:0<6}").parse().ok()?;
+    let micros = int.checked_mul(MICROS_PER_SEC)?.checked_add(frac)?;
+    Some(if negative { -micros } else { micros })
+}
+
+/// Parse `YYYY-MM-DD[ HH:MM:SS[.ffffff]]` (a `T` may separate date and time), with
+/// a year from 0 to 9999
+fn parse_datetime(s: &str) -> Option<i64> {
+    let s = s.trim();
+    let (date, time) = match s.split_once([' ', 'T']) {
+        Some((date, time)) => (date, Some(time)),
+        None => (s, None),
+    };
+
+    let mut date_parts = date.splitn(3, '-');
+    let year: i64 = date_parts.next()?.parse().ok()?;
+    let month: u32 = date_parts.next()?.parse().ok()?;
+    let day: u32 = date_parts.next()?.parse().ok()?;
+    // Limit years to the `DATETIME` range, which also keeps the day count small
+    if !(0..=9999).contains(&year) || !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    // Days past the end of the month roll over, so make sure the date round trips
+    let days = days_from_civil(year, month, day);
+    if civil_from_days(days) != (year, month, day) {
+        return None;
+    }
+
+    let mut time_micros = 0;
+    if let Some(time) = time {
+        let (hms, frac) = time.split_once('.').unwrap_or((time, ""));
+        let mut hms_parts = hms.splitn(3, ':');
+        let hour: i64 = hms_parts.next()?.parse().ok()?;
+        let minute: i64 = hms_parts.next()?.parse().ok()?;
+        let second: i64 = hms_parts.next()?.parse().ok()?;
+        if hour > 23 || minute > 59 || second > 59 {
+            return None;
+        }
+        let frac = parse_decimal_secs(&format!("0.{frac}"))?;
+        time_micros = (hour * 3600 + minute * 60 + second) * MICROS_PER_SEC + frac;
+    }
+
+    days.checked_mul(MICROS_PER_DAY)?.checked_add(time_micros)
+}
+
+/// Get a timestamp argument as microseconds since the Unix epoch. Numbers are Unix
+/// seconds, like `UNIX_TIMESTAMP()` returns, and strings may be a `DATETIME` in
+/// UTC or a number. `None` if the value is null or can't be parsed.
+pub(crate) fn timestamp_arg(value: &SqlResult) -> Option<i64> {
+    match value {
+        SqlResult::Int(Some(secs)) => secs.checked_mul(MICROS_PER_SEC),
+        SqlResult::Real(Some(secs)) => {
+            let micros = (secs * MICROS_PER_SEC as f64).round();
+            (micros.is_finite() && micros.abs() < i64::MAX as f64).then_some(micros as i64)
+        }
+        SqlResult::Decimal(Some(d)) => parse_decimal_secs(d),
+        SqlResult::String(Some(buf)) => {
+            let s = std::str::from_utf8(buf).ok()?;
+            parse_datetime(s).or_else(|| parse_decimal_secs(s))
+        }
+        _ => None,
+    }
+}
+
+/// Check a constant timestamp argument in `init`
+pub(crate) fn timestamp_init(arg: &SqlArg<Init>, fn_name: &str) -> Result<(), String> {
+    let value = arg.value();
+    if let SqlResult::String(None)
+    | SqlResult::Int(None)
+    | SqlResult::Real(None)
+    | SqlResult::Decimal(None) = value
+    {
+        return Ok(());
+    }
+    if timestamp_arg(&value).is_none() {
+        return Err(format!(
+            "{fn_name}: timestamp must be a DATETIME or Unix time in seconds"
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_civil_from_days() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(-1), (1969, 12, 31));
+        assert_eq!(civil_from_days(11_016), (2000, 2, 29));
+        assert_eq!(civil_from_days(-141_427), (1582, 10, 15));
+
+        for days in [-141_427, -1, 0, 11_016, 19_045, 2_932_896] {
+            let (y, m, d) = civil_from_days(days);
+            assert_eq!(days_from_civil(y, m, d), days);
+        }
+    }
+
+    #[test]
+    fn test_timestamp_arg() {
+        let expected = Some(1_645_557_742_000_000);
+        assert_eq!(
+            timestamp_arg(&SqlResult::Int(Some(1_645_557_742))),
+            expected
+        );
+        assert_eq!(
+            timestamp_arg(&SqlResult::Real(Some(1_645_557_742.0))),
+            expected
+        );
+        assert_eq!(
+            timestamp_arg(&SqlResult::Decimal(Some("1645557742.000000"))),
+            expected
+        );
+        assert_eq!(
+            timestamp_arg(&SqlResult::String(Some(b"2022-02-22 19:22:22"))),
+            expected
+        );
+        assert_eq!(
+            timestamp_arg(&SqlResult::String(Some(b"2022-02-22T19:22:22.5"))),
+            Some(1_645_557_742_500_000)
+        );
+        assert_eq!(
+            timestamp_arg(&SqlResult::String(Some(b"1969-12-31"))),
+            Some(-86_400_000_000)
+        );
+        assert_eq!(
+            timestamp_arg(&SqlResult::String(Some(b"1645557742.25"))),
+            Some(1_645_557_742_250_000)
+        );
+
+        assert_eq!(timestamp_arg(&SqlResult::String(Some(b"yesterday"))), None);
+        assert_eq!(timestamp_arg(&SqlResult::String(Some(b"2022-13-01"))), None);
+        assert_eq!(
+            timestamp_arg(&SqlResult::String(Some(b"99999999999999999-01-01"))),
+            None
+        );
+        assert_eq!(
+            timestamp_arg(&SqlResult::String(Some(b"10000-01-01"))),
+            None
+        );
+        assert_eq!(timestamp_arg(&SqlResult::String(Some(b"-1-01-01"))), None);
+        assert_eq!(timestamp_arg(&SqlResult::String(Some(b"2022-02-31"))), None);
+        assert_eq!(timestamp_arg(&SqlResult::String(Some(b"2023-04-31"))), None);
+        assert_eq!(timestamp_arg(&SqlResult::String(Some(b"2023-02-29"))), None);
+        assert_eq!(timestamp_arg(&SqlResult::String(Some(b"1900-02-29"))), None);
+        assert_eq!(
+            timestamp_arg(&SqlResult::String(Some(b"2024-02-29"))),
+            Some(1_709_164_800_000_000)
+        );
+        assert_eq!(
+            timestamp_arg(&SqlResult::String(Some(b"2000-02-29 12:00:00"))),
+            Some(951_825_600_000_000)
+        );
+        assert_eq!(timestamp_arg(&SqlResult::Decimal(Some("1.2345678"))), None);
+        assert_eq!(timestamp_arg(&SqlResult::Int(None)), None);
+    }
+
+    #[test]
+    fn test_write_datetime() {
+        let mut s = String::new();
+        write_datetime(&mut s, -1);
+        assert_eq!(s, "1969-12-31 23:59:59.999999");
+        write_datetime(&mut s, 1_645_557_742_123_456);
+        assert_eq!(s, "2022-02-22 19:22:22.123456");
+    }
+}